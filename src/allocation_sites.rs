@@ -0,0 +1,299 @@
+//! Track where every [`Cc`] has been allocated.
+//!
+//! When the `allocation-sites` feature is enabled, the [`Location`] of the code which called [`Cc::new`]
+//! (or any other constructor) is stored into a side table, leaving the layout of the allocations unchanged.
+//!
+//! [`live_sites`] groups the live objects of the current thread by allocation site, while [`pprof_profile`] exports
+//! the same information as a [pprof](https://github.com/google/pprof) profile, which can be fed into the existing
+//! pprof and flamegraph tooling. Allocation sites are also reported in leak reports, heap dumps and type statistics
+//! when the respective features are enabled.
+//!
+//! Note that the side table adds an overhead to every allocation and deallocation.
+//!
+//! # Example
+//! ```rust
+//!# use rust_cc::*;
+//!# use rust_cc::allocation_sites::*;
+//! let cc = Cc::new(5u32);
+//!
+//! let site = cc.allocation_site().unwrap();
+//! assert_eq!(file!(), site.file());
+//!
+//! let sites = live_sites().unwrap();
+//! assert!(sites.iter().any(|stats| stats.location() == site));
+//! ```
+//!
+//! [`Cc`]: crate::Cc
+//! [`Cc::new`]: crate::Cc::new
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::cmp::Reverse;
+use core::mem::{self, ManuallyDrop};
+use core::panic::Location;
+use core::ptr::NonNull;
+
+use crate::cc::CcBox;
+use crate::state::StateAccessError;
+use crate::utils;
+
+utils::rust_cc_thread_local! {
+    // The table is wrapped in a ManuallyDrop to make SITES not need to be dropped. This way it remains
+    // accessible while other thread locals are being destroyed, so that leak reports can use it at thread exit
+    static SITES: RefCell<ManuallyDrop<BTreeMap<NonNull<CcBox<()>>, Site>>> = const { RefCell::new(ManuallyDrop::new(BTreeMap::new())) };
+}
+
+type Site = &'static Location<'static>;
+type SitedObject = (NonNull<CcBox<()>>, Site);
+
+#[inline]
+pub(crate) fn record(ptr: NonNull<CcBox<()>>, location: Site) {
+    let _ = SITES.try_with(|sites| {
+        if let Ok(mut sites) = sites.try_borrow_mut() {
+            sites.insert(ptr, location);
+        }
+    });
+}
+
+#[inline]
+pub(crate) fn remove(ptr: NonNull<CcBox<()>>) {
+    let _ = SITES.try_with(|sites| {
+        if let Ok(mut sites) = sites.try_borrow_mut() {
+            sites.remove(&ptr);
+
+            // Free the memory of the table when it becomes empty, otherwise it would be leaked at thread exit
+            if sites.is_empty() {
+                drop(mem::take(&mut **sites));
+            }
+        }
+    });
+}
+
+#[inline]
+pub(crate) fn site_of(ptr: NonNull<CcBox<()>>) -> Option<Site> {
+    SITES.try_with(|sites| {
+        sites.try_borrow().ok().and_then(|sites| sites.get(&ptr).copied())
+    }).ok().flatten()
+}
+
+/// Returns a copy of the side table, to avoid keeping it borrowed.
+pub(crate) fn live_objects() -> Result<Vec<SitedObject>, StateAccessError> {
+    SITES.try_with(|sites| {
+        sites.try_borrow()
+             .map(|sites| sites.iter().map(|(ptr, location)| (*ptr, *location)).collect())
+             .map_err(|_| StateAccessError::AccessError)
+    }).unwrap_or(Err(StateAccessError::AccessError))
+}
+
+/// Returns the number of live objects and live bytes allocated at every allocation site by the current thread.
+///
+/// The sites with more live bytes come first.
+pub fn live_sites() -> Result<Vec<SiteStats>, StateAccessError> {
+    Ok(SiteStats::group(live_objects()?.into_iter().map(|(ptr, location)| {
+        (location, unsafe { ptr.as_ref() }.layout().size())
+    })))
+}
+
+/// Returns a [pprof](https://github.com/google/pprof) profile of the objects allocated by the current thread
+/// which are still alive, grouped by allocation site.
+///
+/// The profile is an uncompressed protocol buffer containing two sample types, `inuse_objects` and `inuse_space`.
+/// Every sample has a single frame, representing the allocation site.
+///
+/// # Example
+/// ```rust,no_run
+///# use rust_cc::allocation_sites::*;
+/// std::fs::write("cc.pb", pprof_profile().unwrap()).unwrap();
+/// // Then run: go tool pprof -http=:8080 cc.pb
+/// ```
+pub fn pprof_profile() -> Result<Vec<u8>, StateAccessError> {
+    Ok(pprof::encode(&live_sites()?))
+}
+
+/// The live objects allocated at a single allocation site.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SiteStats {
+    location: &'static Location<'static>,
+    objects: usize,
+    bytes: usize,
+}
+
+impl SiteStats {
+    /// Groups `objects`, given as pairs of allocation site and size, by allocation site.
+    /// The returned sites are sorted by bytes, in descending order.
+    pub(crate) fn group(objects: impl IntoIterator<Item = (Site, usize)>) -> Vec<SiteStats> {
+        let mut sites: BTreeMap<Site, SiteStats> = BTreeMap::new();
+        for (location, size) in objects {
+            let stats = sites.entry(location).or_insert(SiteStats {
+                location,
+                objects: 0,
+                bytes: 0,
+            });
+            stats.objects += 1;
+            stats.bytes += size;
+        }
+
+        let mut sites: Vec<SiteStats> = sites.into_values().collect();
+        // The sort is stable, so sites with the same bytes remain sorted by location
+        sites.sort_by_key(|stats| Reverse(stats.bytes));
+        sites
+    }
+
+    /// Returns the allocation site.
+    #[inline]
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// Returns the number of live objects allocated at this site.
+    #[inline]
+    pub fn objects(&self) -> usize {
+        self.objects
+    }
+
+    /// Returns the number of live bytes allocated at this site.
+    #[inline]
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+/// A minimal encoder for the pprof [profile.proto](https://github.com/google/pprof/blob/main/proto/profile.proto) format.
+mod pprof {
+    use super::*;
+
+    // Field numbers of the Profile message
+    const PROFILE_SAMPLE_TYPE: u64 = 1;
+    const PROFILE_SAMPLE: u64 = 2;
+    const PROFILE_LOCATION: u64 = 4;
+    const PROFILE_FUNCTION: u64 = 5;
+    const PROFILE_STRING_TABLE: u64 = 6;
+
+    // Field numbers of the ValueType message
+    const VALUE_TYPE_TYPE: u64 = 1;
+    const VALUE_TYPE_UNIT: u64 = 2;
+
+    // Field numbers of the Sample message
+    const SAMPLE_LOCATION_ID: u64 = 1;
+    const SAMPLE_VALUE: u64 = 2;
+
+    // Field numbers of the Location message
+    const LOCATION_ID: u64 = 1;
+    const LOCATION_LINE: u64 = 4;
+
+    // Field numbers of the Line message
+    const LINE_FUNCTION_ID: u64 = 1;
+    const LINE_LINE: u64 = 2;
+    const LINE_COLUMN: u64 = 3;
+
+    // Field numbers of the Function message
+    const FUNCTION_ID: u64 = 1;
+    const FUNCTION_NAME: u64 = 2;
+    const FUNCTION_SYSTEM_NAME: u64 = 3;
+    const FUNCTION_FILENAME: u64 = 4;
+
+    const WIRE_VARINT: u64 = 0;
+    const WIRE_LEN: u64 = 2;
+
+    pub(super) fn encode(sites: &[SiteStats]) -> Vec<u8> {
+        let mut strings = StringTable::new();
+        let mut profile = Vec::new();
+
+        for (ty, unit) in [("inuse_objects", "count"), ("inuse_space", "bytes")] {
+            let mut value_type = Vec::new();
+            write_varint_field(&mut value_type, VALUE_TYPE_TYPE, strings.index(ty));
+            write_varint_field(&mut value_type, VALUE_TYPE_UNIT, strings.index(unit));
+            write_len_field(&mut profile, PROFILE_SAMPLE_TYPE, &value_type);
+        }
+
+        for (i, site) in sites.iter().enumerate() {
+            // Ids must be non-zero. Every site has its own location and function, which share the same id
+            let id = i as u64 + 1;
+
+            let mut sample = Vec::new();
+            write_packed_field(&mut sample, SAMPLE_LOCATION_ID, &[id]);
+            write_packed_field(&mut sample, SAMPLE_VALUE, &[site.objects as u64, site.bytes as u64]);
+            write_len_field(&mut profile, PROFILE_SAMPLE, &sample);
+
+            let mut line = Vec::new();
+            write_varint_field(&mut line, LINE_FUNCTION_ID, id);
+            write_varint_field(&mut line, LINE_LINE, site.location.line() as u64);
+            write_varint_field(&mut line, LINE_COLUMN, site.location.column() as u64);
+
+            let mut location = Vec::new();
+            write_varint_field(&mut location, LOCATION_ID, id);
+            write_len_field(&mut location, LOCATION_LINE, &line);
+            write_len_field(&mut profile, PROFILE_LOCATION, &location);
+
+            let name = strings.index(&site.location.to_string());
+            let mut function = Vec::new();
+            write_varint_field(&mut function, FUNCTION_ID, id);
+            write_varint_field(&mut function, FUNCTION_NAME, name);
+            write_varint_field(&mut function, FUNCTION_SYSTEM_NAME, name);
+            write_varint_field(&mut function, FUNCTION_FILENAME, strings.index(site.location.file()));
+            write_len_field(&mut profile, PROFILE_FUNCTION, &function);
+        }
+
+        for string in &strings.strings {
+            write_len_field(&mut profile, PROFILE_STRING_TABLE, string.as_bytes());
+        }
+
+        profile
+    }
+
+    /// The string table of a profile. The first string must always be the empty string.
+    struct StringTable {
+        strings: Vec<String>,
+        indexes: BTreeMap<String, u64>,
+    }
+
+    impl StringTable {
+        fn new() -> Self {
+            let mut table = StringTable {
+                strings: Vec::new(),
+                indexes: BTreeMap::new(),
+            };
+            table.index("");
+            table
+        }
+
+        fn index(&mut self, s: &str) -> u64 {
+            if let Some(&index) = self.indexes.get(s) {
+                return index;
+            }
+            let index = self.strings.len() as u64;
+            self.strings.push(s.to_string());
+            self.indexes.insert(s.to_string(), index);
+            index
+        }
+    }
+
+    fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+        while value >= 0x80 {
+            buf.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        buf.push(value as u8);
+    }
+
+    fn write_varint_field(buf: &mut Vec<u8>, field: u64, value: u64) {
+        write_varint(buf, (field << 3) | WIRE_VARINT);
+        write_varint(buf, value);
+    }
+
+    fn write_len_field(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+        write_varint(buf, (field << 3) | WIRE_LEN);
+        write_varint(buf, bytes.len() as u64);
+        buf.extend_from_slice(bytes);
+    }
+
+    fn write_packed_field(buf: &mut Vec<u8>, field: u64, values: &[u64]) {
+        let mut packed = Vec::new();
+        for &value in values {
+            write_varint(&mut packed, value);
+        }
+        write_len_field(buf, field, &packed);
+    }
+}
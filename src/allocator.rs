@@ -0,0 +1,125 @@
+//! Customize how cycle-collected objects are allocated.
+//!
+//! By default, every [`Cc`] is allocated with the global allocator. A different allocator can be provided by implementing
+//! the [`CcAllocator`] trait. It can be used in two ways:
+//!  * installed for the whole thread using [`set_thread_allocator`], in which case it's used by [`Cc::new`] and for every
+//!    other internal allocation (like `Weak` metadata and `Cleaner` maps);
+//!  * passed to [`Cc::new_in`], in which case it's used only for that single allocation and is stored inside it.
+//!
+//! When the `nightly` feature is enabled, [`CcAllocator`] is implemented for every type which implements
+//! the unstable [`Allocator`] trait.
+//!
+//! # Example
+//! ```rust
+//!# use rust_cc::*;
+//!# use rust_cc::allocator::*;
+//!# use std::alloc::{GlobalAlloc, Layout, System};
+//!# use std::ptr::NonNull;
+//!# use std::sync::atomic::{AtomicUsize, Ordering};
+//! struct Counting(AtomicUsize);
+//!
+//! unsafe impl CcAllocator for Counting {
+//!     fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+//!         self.0.fetch_add(1, Ordering::Relaxed);
+//!         NonNull::new(unsafe { System.alloc(layout) })
+//!     }
+//!
+//!     unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+//!         System.dealloc(ptr.as_ptr(), layout);
+//!     }
+//! }
+//!
+//! static COUNTING: Counting = Counting(AtomicUsize::new(0));
+//!
+//! std::thread::spawn(|| {
+//!     set_thread_allocator(Some(&COUNTING)).unwrap();
+//!     let _cc = Cc::new(5);
+//! }).join().unwrap();
+//!
+//! assert_eq!(1, COUNTING.0.load(Ordering::Relaxed));
+//! ```
+//!
+//! [`Cc`]: crate::Cc
+//! [`Cc::new`]: crate::Cc::new
+//! [`Cc::new_in`]: crate::Cc::new_in
+//! [`Allocator`]: https://doc.rust-lang.org/core/alloc/trait.Allocator.html
+
+use alloc::alloc::Layout;
+use core::ptr::NonNull;
+use thiserror::Error;
+
+use crate::state::{try_state, StateAccessError};
+
+/// An allocator for cycle-collected objects.
+///
+/// # Safety
+///
+/// Memory returned by [`allocate`][`CcAllocator::allocate`] must be valid for reads and writes of `layout.size()` bytes,
+/// must be aligned to `layout.align()` and must remain valid until it's passed to [`deallocate`][`CcAllocator::deallocate`].
+pub unsafe trait CcAllocator {
+    /// Allocates memory as described by the given `layout`, returning [`None`] on failure.
+    ///
+    /// The size of `layout` is never zero.
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>>;
+
+    /// Deallocates the memory referenced by `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by [`allocate`][`CcAllocator::allocate`] on this allocator using the same `layout`.
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+}
+
+#[cfg(feature = "nightly")]
+unsafe impl<A: core::alloc::Allocator + ?Sized> CcAllocator for A {
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        core::alloc::Allocator::allocate(self, layout).ok().map(NonNull::cast)
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        core::alloc::Allocator::deallocate(self, ptr, layout)
+    }
+}
+
+/// Sets the allocator used by the current thread, replacing the previous one.
+///
+/// Passing [`None`] restores the global allocator.
+///
+/// Since memory must be deallocated with the same allocator used to allocate it, the allocator cannot be replaced while
+/// objects allocated with it are still alive. Objects allocated using [`Cc::new_in`][`crate::Cc::new_in`] don't count,
+/// since they're always deallocated with their own allocator.
+///
+/// # Errors
+///
+/// Returns [`SetAllocatorError::InUse`] if objects allocated with the current allocator are still alive.
+#[inline]
+pub fn set_thread_allocator(allocator: Option<&'static dyn CcAllocator>) -> Result<(), SetAllocatorError> {
+    try_state(|state| {
+        if state.allocator_allocations() != 0 {
+            return Err(SetAllocatorError::InUse);
+        }
+        state.set_allocator(allocator);
+        Ok(())
+    }).unwrap_or(Err(SetAllocatorError::AccessError))
+}
+
+/// Returns the allocator used by the current thread, or [`None`] if the global allocator is used.
+#[inline]
+pub fn thread_allocator() -> Result<Option<&'static dyn CcAllocator>, StateAccessError> {
+    try_state(|state| state.allocator())
+}
+
+/// An error returned by [`set_thread_allocator`].
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum SetAllocatorError {
+    /// The garbage collector state couldn't be accessed.
+    #[error("couldn't access the state")]
+    AccessError,
+
+    /// Objects allocated with the current allocator are still alive.
+    #[error("objects allocated with the current allocator are still alive")]
+    InUse,
+}
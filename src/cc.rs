@@ -1,4 +1,6 @@
 use alloc::alloc::Layout;
+#[cfg(any(feature = "graph-walk", feature = "type-stats"))]
+use core::any::TypeId;
 use core::cell::UnsafeCell;
 use core::marker::PhantomData;
 use core::mem;
@@ -12,6 +14,7 @@
     ptr::{metadata, DynMetadata},
 };
 
+use crate::allocator::CcAllocator;
 use crate::counter_marker::{CounterMarker, Mark};
 use crate::state::{replace_state_field, state, State, try_state};
 use crate::trace::{Context, ContextInner, Finalize, Trace};
@@ -47,11 +50,105 @@ impl<T: Trace + 'static> Cc<T> {
     /// 
     /// # Panics
     /// 
-    /// Panics if the automatically-stared collection panics.
+    /// Panics if the automatically-stared collection panics or if the allocation doesn't fit into the heap limit
+    /// (see [`Config::set_max_heap_bytes`][`crate::config::Config::set_max_heap_bytes`]).
     #[inline(always)]
     #[must_use = "newly created Cc is immediately dropped"]
     #[track_caller]
     pub fn new(t: T) -> Cc<T> {
+        // The location must be obtained outside of the closure, since closures cannot be #[track_caller]
+        #[cfg(feature = "allocation-sites")]
+        let location = core::panic::Location::caller();
+
+        state(|state| {
+            #[cfg(debug_assertions)]
+            if state.is_tracing() {
+                panic!("Cannot create a new Cc while tracing!");
+            }
+
+            #[cfg(feature = "auto-collect")]
+            if let Err(error) = super::trigger_collection(state, Layout::new::<CcBox<T>>().size()) {
+                panic!("{}", error);
+            }
+
+            let inner = CcBox::new(t, state);
+            #[cfg(feature = "allocation-sites")]
+            crate::allocation_sites::record(inner.cast(), location);
+
+            Cc {
+                inner,
+                _phantom: PhantomData,
+            }
+        })
+    }
+
+    /// Creates a new `Cc`, returning an error if the allocation doesn't fit into the heap limit.
+    ///
+    /// See [`Config::set_max_heap_bytes`] and [`OutOfMemoryPolicy`] for more details.
+    ///
+    /// # Collection
+    ///
+    /// This method may start a collection, which is always started when the allocation would exceed the heap limit.
+    ///
+    /// See the [`config` module documentation][`mod@crate::config`] for more details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the automatically-stared collection panics or if the [`OutOfMemoryPolicy`] is [`Panic`][`OutOfMemoryPolicy::Panic`].
+    ///
+    /// [`Config::set_max_heap_bytes`]: crate::config::Config::set_max_heap_bytes
+    /// [`OutOfMemoryPolicy`]: crate::config::OutOfMemoryPolicy
+    /// [`OutOfMemoryPolicy::Panic`]: crate::config::OutOfMemoryPolicy::Panic
+    #[cfg(feature = "auto-collect")]
+    #[inline]
+    #[track_caller]
+    pub fn try_new(t: T) -> Result<Cc<T>, crate::config::HeapLimitError> {
+        // The location must be obtained outside of the closure, since closures cannot be #[track_caller]
+        #[cfg(feature = "allocation-sites")]
+        let location = core::panic::Location::caller();
+
+        state(|state| {
+            #[cfg(debug_assertions)]
+            if state.is_tracing() {
+                panic!("Cannot create a new Cc while tracing!");
+            }
+
+            super::trigger_collection(state, Layout::new::<CcBox<T>>().size())?;
+
+            let inner = CcBox::new(t, state);
+            #[cfg(feature = "allocation-sites")]
+            crate::allocation_sites::record(inner.cast(), location);
+
+            Ok(Cc {
+                inner,
+                _phantom: PhantomData,
+            })
+        })
+    }
+
+    /// Creates a new `Cc` allocated using the provided allocator.
+    ///
+    /// The allocator is stored inside the allocation and is used to deallocate it, regardless of
+    /// the allocator installed for the thread (see the [`allocator` module documentation][`mod@crate::allocator`]).
+    /// 
+    /// # Collection
+    /// 
+    /// This method may start a collection when the `auto-collect` feature is enabled.
+    /// 
+    /// See the [`config` module documentation][`mod@crate::config`] for more details.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the automatically-stared collection panics or if the allocation doesn't fit into the heap limit
+    /// (see [`Config::set_max_heap_bytes`][`crate::config::Config::set_max_heap_bytes`]).
+    #[inline(always)]
+    #[must_use = "newly created Cc is immediately dropped"]
+    #[track_caller]
+    pub fn new_in<A: CcAllocator + 'static>(t: T, alloc: A) -> Cc<T> {
+        // The location must be obtained outside of the closure, since closures cannot be #[track_caller]
+        #[cfg(feature = "allocation-sites")]
+        let location = core::panic::Location::caller();
+
         state(|state| {
             #[cfg(debug_assertions)]
             if state.is_tracing() {
@@ -59,10 +156,16 @@ pub fn new(t: T) -> Cc<T> {
             }
 
             #[cfg(feature = "auto-collect")]
-            super::trigger_collection();
+            if let Err(error) = super::trigger_collection(state, Layout::new::<CcBoxIn<T, A>>().size()) {
+                panic!("{}", error);
+            }
+
+            let inner = CcBox::new_in(t, alloc, state);
+            #[cfg(feature = "allocation-sites")]
+            crate::allocation_sites::record(inner.cast(), location);
 
             Cc {
-                inner: CcBox::new(t, state),
+                inner,
                 _phantom: PhantomData,
             }
         })
@@ -155,6 +258,65 @@ pub fn mark_alive(&self) {
         remove_from_list(self.inner.cast());
     }
 
+    /// Returns the size in bytes of the allocations reachable from this [`Cc`], including the pointed allocation.
+    ///
+    /// The reachable allocations are discovered using [`Trace`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if called during a collection.
+    #[cfg(feature = "graph-walk")]
+    #[inline]
+    #[track_caller]
+    pub fn reachable_size(&self) -> usize {
+        crate::introspection::reachable_size(self.inner.cast())
+    }
+
+    /// Returns the size in bytes of the allocations which would be freed if this [`Cc`] was dropped.
+    ///
+    /// An allocation reachable from this [`Cc`] is retained by it if every reference to the allocation comes from other
+    /// retained allocations, i.e. if it isn't referenced from anywhere else. Note that retained allocations which are part
+    /// of a cycle are freed by the next collection, not immediately when this [`Cc`] is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called during a collection.
+    #[cfg(feature = "graph-walk")]
+    #[inline]
+    #[track_caller]
+    pub fn retained_size(&self) -> usize {
+        crate::introspection::retained_size(self.inner.cast())
+    }
+
+    /// Calls `visitor` with a [`CcRef`] to every object directly pointed by this [`Cc`].
+    ///
+    /// The pointed objects are discovered using [`Trace`], so an object is visited once for every time it is traced.
+    /// Use [`walk_reachable`] to visit every reachable object only once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called during a collection.
+    ///
+    /// [`CcRef`]: crate::introspection::CcRef
+    /// [`walk_reachable`]: crate::introspection::walk_reachable
+    #[cfg(feature = "graph-walk")]
+    #[inline]
+    #[track_caller]
+    pub fn visit_edges(&self, visitor: impl FnMut(crate::introspection::CcRef<'_, dyn Trace>)) {
+        crate::introspection::visit_edges(self, self.inner.cast(), "Cc::visit_edges", visitor);
+    }
+
+    /// Returns the location of the code which allocated the pointed object.
+    ///
+    /// Returns [`None`] if the allocation site isn't available, for example because the thread is exiting.
+    ///
+    /// See the [`allocation_sites` module documentation][`mod@crate::allocation_sites`] for more details.
+    #[cfg(feature = "allocation-sites")]
+    #[inline]
+    pub fn allocation_site(&self) -> Option<&'static core::panic::Location<'static>> {
+        crate::allocation_sites::site_of(self.inner.cast())
+    }
+
     #[inline(always)]
     fn counter_marker(&self) -> &CounterMarker {
         &self.inner().counter_marker
@@ -165,13 +327,13 @@ pub(crate) fn inner(&self) -> &CcBox<T> {
         unsafe { self.inner.as_ref() }
     }
 
-    #[cfg(feature = "weak-ptr")]
+    #[cfg(any(feature = "weak-ptr", feature = "graph-walk"))]
     #[inline(always)]
     pub(crate) fn inner_ptr(&self) -> NonNull<CcBox<T>> {
         self.inner
     }
 
-    #[cfg(feature = "weak-ptr")] // Currently used only here
+    #[cfg(any(feature = "weak-ptr", feature = "graph-walk"))]
     #[inline(always)]
     #[must_use]
     pub(crate) fn __new_internal(inner: NonNull<CcBox<T>>) -> Cc<T> {
@@ -251,6 +413,12 @@ fn handle_possible_cycle<T: ?Sized + Trace + 'static>(cc: &Cc<T>) {
 
             // We know that we're not part of either root_list or non_root_list, since the cc isn't traced
             add_to_list(cc.inner.cast());
+
+            // If the thread's last collection has already been executed, there will be no other chance to collect cc
+            #[cfg(feature = "std")]
+            if try_state(|state| state.is_thread_exited()).unwrap_or(false) {
+                crate::schedule_late_collection();
+            }
         }
 
         // A CcBox can be marked traced only during collections while being into a list different than POSSIBLE_CYCLES.
@@ -351,31 +519,63 @@ impl<T: Trace + 'static> CcBox<T> {
     fn new(t: T, state: &State) -> NonNull<CcBox<T>> {
         let layout = Layout::new::<CcBox<T>>();
 
-        #[cfg(feature = "finalization")]
-        let already_finalized = state.is_finalizing();
-        #[cfg(not(feature = "finalization"))]
-        let already_finalized = false;
-
         unsafe {
             let ptr: NonNull<CcBox<T>> = cc_alloc(layout, state);
             ptr::write(
                 ptr.as_ptr(),
-                CcBox {
-                    next: UnsafeCell::new(None),
-                    prev: UnsafeCell::new(None),
-                    #[cfg(feature = "nightly")]
-                    vtable: metadata(ptr.as_ptr() as *mut dyn InternalTrace),
-                    #[cfg(not(feature = "nightly"))]
-                    fat_ptr: NonNull::new_unchecked(ptr.as_ptr() as *mut dyn InternalTrace),
-                    counter_marker: CounterMarker::new_with_counter_to_one(already_finalized),
-                    _phantom: PhantomData,
-                    elem: UnsafeCell::new(t),
-                },
+                CcBox::new_value(t, ptr.as_ptr() as *mut dyn InternalTrace, state),
             );
+            #[cfg(feature = "type-stats")]
+            crate::type_stats::record_allocation(ptr.cast(), layout.size());
             ptr
         }
     }
 
+    #[inline(always)]
+    #[must_use]
+    fn new_in<A: CcAllocator + 'static>(t: T, alloc: A, state: &State) -> NonNull<CcBox<T>> {
+        let layout = Layout::new::<CcBoxIn<T, A>>();
+
+        unsafe {
+            let ptr: NonNull<CcBoxIn<T, A>> = cc_alloc_in(&alloc, layout, state);
+            ptr::write(
+                ptr.as_ptr(),
+                CcBoxIn {
+                    cc_box: CcBox::new_value(t, ptr.as_ptr() as *mut dyn InternalTrace, state),
+                    alloc,
+                },
+            );
+            #[cfg(feature = "type-stats")]
+            crate::type_stats::record_allocation(ptr.cast(), layout.size());
+            // This cast is correct since CcBoxIn is repr(C) and the CcBox is its first field
+            ptr.cast()
+        }
+    }
+
+    /// SAFETY: `traceable` must point to the allocation which will contain the returned CcBox.
+    #[inline(always)]
+    unsafe fn new_value(t: T, traceable: *mut dyn InternalTrace, state: &State) -> CcBox<T> {
+        #[cfg(feature = "finalization")]
+        let already_finalized = state.is_finalizing() && !finalizes_created_objects();
+        #[cfg(not(feature = "finalization"))]
+        let already_finalized = {
+            let _ = state;
+            false
+        };
+
+        CcBox {
+            next: UnsafeCell::new(None),
+            prev: UnsafeCell::new(None),
+            #[cfg(feature = "nightly")]
+            vtable: metadata(traceable),
+            #[cfg(not(feature = "nightly"))]
+            fat_ptr: NonNull::new_unchecked(traceable),
+            counter_marker: CounterMarker::new_with_counter_to_one(already_finalized),
+            _phantom: PhantomData,
+            elem: UnsafeCell::new(t),
+        }
+    }
+
     #[inline(always)]
     #[cfg(all(test, feature = "std"))] // Only used in unit tests
     #[must_use]
@@ -438,6 +638,55 @@ fn finalize(&self) {
     }
 }
 
+/// A [`CcBox`] allocated by [`Cc::new_in`], followed by the allocator used to allocate it.
+///
+/// The vtable stored inside the CcBox is the one of CcBoxIn, which makes it possible to deallocate using the right
+/// allocator without making every other CcBox bigger.
+#[repr(C)]
+struct CcBoxIn<T: Trace + 'static, A: CcAllocator + 'static> {
+    cc_box: CcBox<T>,
+    alloc: A,
+}
+
+impl<T: Trace + 'static, A: CcAllocator + 'static> CcBoxIn<T, A> {
+    /// SAFETY: `ptr` must point to a CcBoxIn<T, A> which has been allocated with `layout` and whose elem has already been dropped or moved out.
+    unsafe fn dealloc(ptr: NonNull<u8>, layout: Layout, _: &State) {
+        let ptr: NonNull<Self> = ptr.cast();
+
+        // Move the allocator out of the allocation before deallocating it
+        let alloc = ptr::read(ptr::addr_of!((*ptr.as_ptr()).alloc));
+        alloc.deallocate(ptr.cast(), layout);
+    }
+}
+
+unsafe impl<T: Trace + 'static, A: CcAllocator + 'static> Trace for CcBoxIn<T, A> {
+    #[inline(always)]
+    fn trace(&self, ctx: &mut Context<'_>) {
+        self.cc_box.trace(ctx);
+    }
+}
+
+impl<T: Trace + 'static, A: CcAllocator + 'static> Finalize for CcBoxIn<T, A> {
+    #[inline(always)]
+    fn finalize(&self) {
+        self.cc_box.finalize();
+    }
+}
+
+/// Returns whether the objects created during finalization should be finalized (see `FinalizationPolicy`).
+#[cfg(feature = "finalization")]
+#[cold]
+fn finalizes_created_objects() -> bool {
+    #[cfg(feature = "auto-collect")]
+    {
+        crate::config::config(|config| config.finalization_policy().finalize_created_objects()).unwrap_or(false)
+    }
+    #[cfg(not(feature = "auto-collect"))]
+    {
+        false
+    }
+}
+
 #[inline]
 pub(crate) fn remove_from_list(ptr: NonNull<CcBox<()>>) {
     let counter_marker = unsafe { ptr.as_ref() }.counter_marker();
@@ -453,6 +702,9 @@ pub(crate) fn remove_from_list(ptr: NonNull<CcBox<()>>) {
 
             counter_marker.mark(Mark::NonMarked);
             list.remove(ptr);
+
+            #[cfg(feature = "global-stats")]
+            crate::global_stats::record_buffered_objects_count(list.size());
         });
     } else {
         // ptr is not in the list
@@ -491,12 +743,18 @@ pub(crate) fn add_to_list(ptr: NonNull<CcBox<()>>) {
 
             // Mark it
             counter_marker.mark(Mark::PossibleCycles);
+
+            #[cfg(feature = "type-stats")]
+            crate::type_stats::record_buffered(ptr);
         }
         // Add to the list
         //
         // Make sure this operation is the first after the if-else, since the CcBox is in
         // an invalid state now (it's marked Mark::PossibleCycles, but it isn't into the list)
         list.add(ptr);
+
+        #[cfg(feature = "global-stats")]
+        crate::global_stats::record_buffered_objects_count(list.size());
     });
 }
 
@@ -531,12 +789,45 @@ pub(super) unsafe fn drop_inner(ptr: NonNull<Self>) {
         CcBox::get_traceable(ptr).as_mut().drop_elem();
     }
 
+    #[cfg(any(feature = "graph-walk", feature = "type-stats"))]
+    #[inline]
+    pub(crate) fn type_name(ptr: NonNull<Self>) -> &'static str {
+        unsafe {
+            CcBox::get_traceable(ptr).as_ref().type_name()
+        }
+    }
+
+    #[cfg(any(feature = "graph-walk", feature = "type-stats"))]
+    #[inline]
+    pub(crate) fn type_id(ptr: NonNull<Self>) -> TypeId {
+        unsafe {
+            CcBox::get_traceable(ptr).as_ref().type_id()
+        }
+    }
+
+    /// Drops a strong reference to `ptr`, like dropping a [`Cc`] pointing to it.
+    ///
+    /// Safety: the strong reference must be owned by the caller
+    #[cfg(feature = "graph-walk")]
+    #[inline]
+    pub(crate) unsafe fn drop_strong(ptr: NonNull<Self>) {
+        let dropper = CcBox::get_traceable(ptr).as_ref().strong_dropper();
+        dropper(ptr);
+    }
+
+    #[inline]
+    pub(crate) fn deallocator(ptr: NonNull<Self>) -> Deallocator {
+        unsafe {
+            CcBox::get_traceable(ptr).as_ref().deallocator()
+        }
+    }
+
     #[inline]
     fn get_traceable(ptr: NonNull<Self>) -> NonNull<dyn InternalTrace> {
         #[cfg(feature = "nightly")]
         unsafe {
             let vtable = ptr.as_ref().vtable;
-            NonNull::from_raw_parts(ptr.cast(), vtable)
+            NonNull::from_raw_parts(ptr.cast::<()>(), vtable)
         }
 
         #[cfg(not(feature = "nightly"))]
@@ -565,8 +856,13 @@ pub(super) fn start_tracing(ptr: NonNull<Self>, ctx: &mut Context<'_>) {
 
                 // Nothing to do here, ptr is already unmarked
                 debug_assert!(counter_marker.is_not_marked());
+
+                ctx.record_root();
             },
+            #[cfg(feature = "graph-walk")]
+            ContextInner::Introspection { .. } => unreachable!("start_tracing is never used when introspecting"),
         }
+        ctx.record_traced();
 
         // ptr is surely to trace
         //
@@ -617,6 +913,8 @@ fn non_root(counter_marker: &CounterMarker) -> bool {
                     // before ptr is actually added to root_list or non_root_list
                     counter_marker.mark(Mark::Traced);
 
+                    ctx.record_traced();
+
                     // Continue tracing
                     true
                 } else {
@@ -647,8 +945,11 @@ fn non_root(counter_marker: &CounterMarker) -> bool {
                         non_root_list.remove(ptr);
                     } else {
                         root_list.remove(ptr);
+                        ctx.record_root();
                     }
 
+                    ctx.record_traced();
+
                     // Continue root tracing
                     true
                 } else {
@@ -656,6 +957,13 @@ fn non_root(counter_marker: &CounterMarker) -> bool {
                     false
                 }
             },
+            #[cfg(feature = "graph-walk")]
+            ContextInner::Introspection { edges } => {
+                edges.push(ptr);
+
+                // Only the edges of the introspected object are collected
+                false
+            },
         }
     }
 }
@@ -668,9 +976,34 @@ trait InternalTrace: Trace {
 
     /// Safety: see `drop_in_place`
     unsafe fn drop_elem(&self);
+
+    // A function is returned instead of directly deallocating, since &self must not be alive while deallocating
+    fn deallocator(&self) -> Deallocator;
+
+    #[cfg(any(feature = "graph-walk", feature = "type-stats"))]
+    fn type_name(&self) -> &'static str;
+
+    #[cfg(any(feature = "graph-walk", feature = "type-stats"))]
+    fn type_id(&self) -> TypeId;
+
+    // Like deallocator, a function is returned since &self must not be alive while dropping the last reference
+    #[cfg(feature = "graph-walk")]
+    fn strong_dropper(&self) -> StrongDropper;
+}
+
+/// A function which deallocates a CcBox.
+pub(crate) type Deallocator = unsafe fn(NonNull<u8>, Layout, &State);
+
+/// A function which drops a strong reference to a CcBox, like dropping a [`Cc`].
+#[cfg(feature = "graph-walk")]
+pub(crate) type StrongDropper = unsafe fn(NonNull<CcBox<()>>);
+
+#[cfg(feature = "graph-walk")]
+unsafe fn drop_strong<T: Trace + 'static>(ptr: NonNull<CcBox<()>>) {
+    drop(Cc::<T>::__new_internal(ptr.cast()));
 }
 
-impl<T: ?Sized + Trace + 'static> InternalTrace for CcBox<T> {
+impl<T: Trace + 'static> InternalTrace for CcBox<T> {
     #[cfg(feature = "finalization")]
     fn finalize_elem(&self) {
         self.get_elem().finalize();
@@ -679,4 +1012,54 @@ fn finalize_elem(&self) {
     unsafe fn drop_elem(&self) {
         drop_in_place(self.get_elem_mut());
     }
+
+    fn deallocator(&self) -> Deallocator {
+        thread_dealloc
+    }
+
+    #[cfg(any(feature = "graph-walk", feature = "type-stats"))]
+    fn type_name(&self) -> &'static str {
+        core::any::type_name::<T>()
+    }
+
+    #[cfg(any(feature = "graph-walk", feature = "type-stats"))]
+    fn type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    #[cfg(feature = "graph-walk")]
+    fn strong_dropper(&self) -> StrongDropper {
+        drop_strong::<T>
+    }
+}
+
+impl<T: Trace + 'static, A: CcAllocator + 'static> InternalTrace for CcBoxIn<T, A> {
+    #[cfg(feature = "finalization")]
+    fn finalize_elem(&self) {
+        self.cc_box.finalize_elem();
+    }
+
+    unsafe fn drop_elem(&self) {
+        self.cc_box.drop_elem();
+    }
+
+    fn deallocator(&self) -> Deallocator {
+        CcBoxIn::<T, A>::dealloc
+    }
+
+    #[cfg(any(feature = "graph-walk", feature = "type-stats"))]
+    fn type_name(&self) -> &'static str {
+        self.cc_box.type_name()
+    }
+
+    #[cfg(any(feature = "graph-walk", feature = "type-stats"))]
+    fn type_id(&self) -> TypeId {
+        self.cc_box.type_id()
+    }
+
+    // The CcBox is the first field of CcBoxIn, so the deallocator of CcBoxIn is still used (see CcBox::deallocator)
+    #[cfg(feature = "graph-walk")]
+    fn strong_dropper(&self) -> StrongDropper {
+        self.cc_box.strong_dropper()
+    }
 }
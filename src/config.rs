@@ -1,12 +1,15 @@
 //! Configuration of the garbage collector.
 //!
-//! The configuration can be accessed using the [`config`][`fn@config`] function.
+//! The configuration can be accessed using the [`config`][`fn@config`] function. A whole configuration, usually created
+//! and validated using [`Config::builder`], can be installed using [`replace`][`fn@replace`], or only temporarily using
+//! [`scoped`][`fn@scoped`].
 //!
 //! # Automatic collection executions
 //!
 //! Collections can be automatically started if [`auto_collect`][`fn@Config::auto_collect`] is set to `true`.
 //!
-//! To determine whether to start a collection, a *threshold* is kept over the number of allocated bytes.
+//! To determine whether to start a collection, a *threshold* is kept over the number of allocated bytes. External memory
+//! reported using [`report_external_allocation`][`fn@crate::state::report_external_allocation`] is counted as allocated.
 //! When calling a function which may start a collection (e.g. [`Cc::new`][`crate::Cc::new`]),
 //! if the number of allocated bytes exceeds the *threshold* a collection is started.
 //!
@@ -16,19 +19,125 @@
 //! Instead, if the number of allocated bytes exceed the *threshold* multiplied by the [`adjustment_percent`][`fn@Config::adjustment_percent`],
 //! then the *threshold* is halved until the condition becomes true.
 //!
+//! The *threshold* never goes below the [`min_bytes_threshold`][`fn@Config::min_bytes_threshold`] (100 bytes by default)
+//! and never goes above the [`max_bytes_threshold`][`fn@Config::max_bytes_threshold`] (unlimited by default).
+//!
+//! Alternatively, the [`GrowthStrategy::Proportional`] strategy can be selected using [`set_growth_strategy`][`fn@Config::set_growth_strategy`].
+//! In this case, at the end of every collection the *threshold* is set to the number of bytes found alive by the collection
+//! increased by the provided percent, like `GOGC` does in Go. For example, with a percent of 100 the next collection is
+//! started when the allocated bytes are double the bytes found alive by the previous collection.
+//!
 //! Finally, a collection may also happen if the number of objects buffered to be processed in the next collection (see [`Cc::mark_alive`][`crate::Cc::mark_alive`])
 //! exceeds the [`buffered_objects_threshold`][`fn@Config::buffered_objects_threshold`]. This parameter is disabled by default, but can be enabled by
 //! using [`set_buffered_objects_threshold`][`fn@Config::set_buffered_objects_threshold`].
+//!
+//! # Collection policies
+//!
+//! The behavior described above is implemented by the default [`ThresholdPolicy`]. A different [`CollectionPolicy`]
+//! can be installed using [`set_policy`][`fn@Config::set_policy`], for example to start collections after a certain
+//! number of allocations or after some time has passed since the last collection.
+//!
+//! # Suppressing automatic collections
+//!
+//! Automatic collections can be temporarily suppressed using [`no_collect`][`crate::no_collect`] or a
+//! [`NoCollectGuard`][`crate::NoCollectGuard`], for example while holding [`RefCell`] borrows which finalizers or
+//! destructors may try to access. A collection which becomes due meanwhile is handled as set using
+//! [`set_deferred_collection`][`fn@Config::set_deferred_collection`].
+//!
+//! # Heap limit
+//!
+//! A limit on the number of allocated bytes can be set using [`set_max_heap_bytes`][`fn@Config::set_max_heap_bytes`].
+//! The external bytes reported using [`report_external_allocation`][`crate::state::report_external_allocation`] count toward the limit.
+//! When allocating a new [`Cc`][`crate::Cc`] would exceed the limit, a collection is forced first. If the new allocation
+//! still doesn't fit, the [`OutOfMemoryPolicy`] set using [`set_out_of_memory_policy`][`fn@Config::set_out_of_memory_policy`]
+//! is applied. [`Cc::try_new`][`crate::Cc::try_new`] can be used to handle the error instead of panicking.
+//!
+//! # Pause target
+//!
+//! When the `std` feature is enabled, a maximum pause for automatically started collections can be set using
+//! [`set_max_pause`][`fn@Config::set_max_pause`]. The collector measures how much time previous collections spent per
+//! traced object and stops taking new objects from the buffer of possible cycles when the next ones wouldn't fit into
+//! the pause. The remaining objects are processed by the next collections, which are started as soon as a collection may
+//! be automatically started again (see [`CollectionTrigger::Resumed`]). The target and the achieved pauses are reported
+//! by [`CollectionReport::pause_target`] and [`CollectionReport::duration`].
+//!
+//! Note that the pause is a best-effort target and may be exceeded, since the objects reachable from a buffered object
+//! are always traced together and finalization and dropping aren't interruptible.
+//!
+//! # Finalization
+//!
+//! When the `finalization` feature is enabled, a collection finalizes the garbage objects it finds and then re-executes
+//! the collection algorithm to detect the objects resurrected by the finalizers. Since finalizers may create new garbage,
+//! this may happen more than once. The [`FinalizationPolicy`] set using [`set_finalization_policy`][`fn@Config::set_finalization_policy`]
+//! limits the number of rounds per collection, decides what to do with the objects left after the last round and whether
+//! the objects created by finalizers are finalized too.
+//!
+//! # Environment variables
+//!
+//! When the `std` feature is enabled, [`from_env`][`fn@from_env`] reads a configuration from the following environment variables:
+//!
+//! | Variable                           | Parameter                                                                    |
+//! |------------------------------------|------------------------------------------------------------------------------|
+//! | `RUST_CC_AUTO_COLLECT`             | [`auto_collect`][`fn@Config::auto_collect`] (`true` or `false`)              |
+//! | `RUST_CC_BYTES_THRESHOLD`          | The initial *threshold*                                                      |
+//! | `RUST_CC_ADJUSTMENT_PERCENT`       | [`adjustment_percent`][`fn@Config::adjustment_percent`]                      |
+//! | `RUST_CC_BUFFERED_THRESHOLD`       | [`buffered_objects_threshold`][`fn@Config::buffered_objects_threshold`] (or `none`) |
+//! | `RUST_CC_MIN_BYTES_THRESHOLD`      | [`min_bytes_threshold`][`fn@Config::min_bytes_threshold`]                    |
+//! | `RUST_CC_MAX_BYTES_THRESHOLD`      | [`max_bytes_threshold`][`fn@Config::max_bytes_threshold`]                    |
+//! | `RUST_CC_GROWTH_STRATEGY`          | [`growth_strategy`][`fn@Config::growth_strategy`] (`doubling` or `proportional:<percent>`) |
+//! | `RUST_CC_MAX_HEAP_BYTES`           | [`max_heap_bytes`][`fn@Config::max_heap_bytes`] (or `none`)                  |
+//! | `RUST_CC_OUT_OF_MEMORY_POLICY`     | [`out_of_memory_policy`][`fn@Config::out_of_memory_policy`] (`panic` or `error`) |
+//! | `RUST_CC_MAX_PAUSE_MICROS`         | [`max_pause`][`fn@Config::max_pause`] in microseconds (or `none`)            |
+//! | `RUST_CC_DEFERRED_COLLECTION`      | [`deferred_collection`][`fn@Config::deferred_collection`] (`run-immediately` or `wait-for-next-trigger`) |
+//! | `RUST_CC_FINALIZATION_MAX_ROUNDS`  | `max_rounds` of the [finalization policy](#finalization)                     |
+//! | `RUST_CC_FINALIZE_CREATED_OBJECTS` | `finalize_created_objects` of the [finalization policy](#finalization) (`true` or `false`) |
+//! | `RUST_CC_LEFTOVER_OBJECTS`         | `leftover_objects` of the [finalization policy](#finalization) (`keep` or `drop-without-finalizing`) |
+//!
+//! The finalization variables are read only when the `finalization` feature is enabled. Variables which aren't set leave
+//! the respective parameter to its default value. [`Config::to_env_string`] returns
+//! the variables describing a configuration.
+//!
+//! When the `env-config` feature is enabled, the environment variables are also automatically applied to the configuration
+//! of every thread the first time it is accessed, on top of the [process-wide default](#process-wide-configuration).
+//! If the environment variables are malformed, none of them is applied and the error is returned by
+#![cfg_attr(feature = "env-config", doc = "[`env_error`][`fn@env_error`].")]
+#![cfg_attr(not(feature = "env-config"), doc = "`env_error`.")]
+//!
+//! # Process-wide configuration
+//!
+//! Every thread has its own configuration. When the `std` feature is enabled, a process-wide default can be set using
+//! [`set_default`][`fn@set_default`]. Threads which haven't accessed their configuration yet copy the default one on first access.
+//!
+//! To change the configuration of every thread, use [`broadcast`][`fn@broadcast`] instead. The provided configuration
+//! is applied by every thread the next time it accesses its configuration, which usually happens when creating a new
+//! [`Cc`][`crate::Cc`] or when a collection is executed.
 
+use alloc::boxed::Box;
 use alloc::rc::Rc;
+#[cfg(feature = "std")]
+use alloc::format;
+#[cfg(feature = "std")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "std")]
+use alloc::{vec, vec::Vec};
 use core::cell::RefCell;
+use core::fmt::Debug;
+#[cfg(feature = "std")]
+use core::cell::Cell;
+#[cfg(feature = "std")]
+use core::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "std")]
+use std::sync::{PoisonError, RwLock};
+#[cfg(feature = "std")]
+use std::time::Duration;
+use core::mem::{self, ManuallyDrop};
 use core::num::NonZeroUsize;
 use core::marker::PhantomData;
 
 use thiserror::Error;
 use crate::list::CountedList;
 
-use crate::state::State;
+use crate::state::{CollectionReport, CollectionTrigger, State};
 use crate::utils;
 
 const DEFAULT_BYTES_THRESHOLD: usize = 100;
@@ -37,6 +146,34 @@
     pub(crate) static CONFIG: RefCell<Config> = const { RefCell::new(Config::new()) };
 }
 
+#[cfg(feature = "std")]
+utils::rust_cc_thread_local! {
+    // The generation of the process-wide configuration last applied to CONFIG, None if it has never been applied
+    static APPLIED_GENERATION: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+#[cfg(feature = "env-config")]
+utils::rust_cc_thread_local! {
+    // The error returned applying the environment variables to CONFIG, if any
+    static ENV_ERROR: RefCell<Option<EnvConfigError>> = const { RefCell::new(None) };
+}
+
+#[cfg(feature = "std")]
+static DEFAULT_CONFIG: RwLock<DefaultConfig> = RwLock::new(DefaultConfig {
+    config: None,
+    generation: 0,
+});
+
+// Mirrors DEFAULT_CONFIG.generation, used to avoid locking DEFAULT_CONFIG on every configuration access
+#[cfg(feature = "std")]
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(feature = "std")]
+struct DefaultConfig {
+    config: Option<SharedConfig>,
+    generation: usize,
+}
+
 /// Access the configuration.
 ///
 /// Returns [`Err`] if the configuration is already being accessed.
@@ -60,10 +197,329 @@ pub fn config<F, R>(f: F) -> Result<R, ConfigAccessError>
         config
         .try_borrow_mut()
         .or(Err(ConfigAccessError::ConcurrentAccessError))
-        .map(|mut config| f(&mut config))
+        .map(|mut config| {
+            #[cfg(feature = "std")]
+            apply_default(&mut config);
+
+            f(&mut config)
+        })
     }).unwrap_or(Err(ConfigAccessError::AccessError))
 }
 
+/// Replaces the configuration of the current thread with `new`, returning the previous one.
+///
+/// Returns [`Err`] if the configuration is already being accessed.
+///
+/// # Example
+/// ```rust
+///# use rust_cc::config::*;
+/// let old = replace(Config::builder().auto_collect(false).build().unwrap()).unwrap();
+/// assert!(!config(|config| config.auto_collect()).unwrap());
+///
+/// replace(old).unwrap();
+/// ```
+pub fn replace(new: Config) -> Result<Config, ConfigAccessError> {
+    config(|config| mem::replace(config, new))
+}
+
+/// Executes `f` using `temp` as the configuration of the current thread, then restores the previous configuration.
+///
+/// The previous configuration is restored even if `f` panics. Changes made to the configuration inside `f` are discarded.
+///
+/// Returns [`Err`] if the configuration is already being accessed, in which case `f` isn't executed.
+///
+/// # Example
+/// ```rust
+///# use rust_cc::config::*;
+/// let temp = Config::builder().auto_collect(false).build().unwrap();
+/// scoped(temp, || {
+///     assert!(!config(|config| config.auto_collect()).unwrap());
+/// }).unwrap();
+///
+/// assert!(config(|config| config.auto_collect()).unwrap());
+/// ```
+pub fn scoped<F, R>(temp: Config, f: F) -> Result<R, ConfigAccessError>
+where
+    F: FnOnce() -> R,
+{
+    struct RestoreGuard(Option<Config>);
+
+    impl Drop for RestoreGuard {
+        #[inline]
+        fn drop(&mut self) {
+            if let Some(old) = self.0.take() {
+                let _ = replace(old);
+            }
+        }
+    }
+
+    let _guard = RestoreGuard(Some(replace(temp)?));
+    Ok(f())
+    // _guard is dropped here, restoring the previous configuration
+}
+
+/// Sets the process-wide default configuration.
+///
+/// Threads which haven't accessed their configuration yet will use a copy of `config` as their configuration.
+/// The configuration of the other threads is left untouched, use [`broadcast`][`fn@broadcast`] to also change it.
+///
+/// See the [module-level documentation][`mod@crate::config`] for more details.
+///
+/// # Example
+/// ```rust
+///# use rust_cc::config::*;
+/// let mut default = Config::default();
+/// default.set_auto_collect(false);
+/// set_default(default);
+///
+/// std::thread::spawn(|| {
+///     assert!(!config(|config| config.auto_collect()).unwrap());
+/// }).join().unwrap();
+/// ```
+#[cfg(feature = "std")]
+pub fn set_default(config: Config) {
+    let mut default = DEFAULT_CONFIG.write().unwrap_or_else(PoisonError::into_inner);
+    default.config = Some(config.to_shared());
+}
+
+/// Sets the process-wide default configuration and applies it to every thread.
+///
+/// Every thread copies `config` into its configuration the next time it accesses it. Threads which haven't accessed
+/// their configuration yet will use a copy of `config` as their configuration, like with [`set_default`][`fn@set_default`].
+///
+/// See the [module-level documentation][`mod@crate::config`] for more details.
+#[cfg(feature = "std")]
+pub fn broadcast(config: Config) {
+    let mut default = DEFAULT_CONFIG.write().unwrap_or_else(PoisonError::into_inner);
+    default.config = Some(config.to_shared());
+    default.generation = default.generation.wrapping_add(1);
+    GENERATION.store(default.generation, Ordering::Release);
+}
+
+/// Returns a copy of the process-wide default configuration, or [`None`] if it has never been set.
+///
+/// See the [module-level documentation][`mod@crate::config`] for more details.
+#[cfg(feature = "std")]
+pub fn default_config() -> Option<Config> {
+    let default = DEFAULT_CONFIG.read().unwrap_or_else(PoisonError::into_inner);
+    default.config.as_ref().map(|shared| {
+        let mut config = Config::new();
+        config.apply_shared(shared);
+        config
+    })
+}
+
+#[cfg(feature = "std")]
+const ENV_AUTO_COLLECT: &str = "RUST_CC_AUTO_COLLECT";
+#[cfg(feature = "std")]
+const ENV_BYTES_THRESHOLD: &str = "RUST_CC_BYTES_THRESHOLD";
+#[cfg(feature = "std")]
+const ENV_ADJUSTMENT_PERCENT: &str = "RUST_CC_ADJUSTMENT_PERCENT";
+#[cfg(feature = "std")]
+const ENV_BUFFERED_THRESHOLD: &str = "RUST_CC_BUFFERED_THRESHOLD";
+#[cfg(feature = "std")]
+const ENV_MIN_BYTES_THRESHOLD: &str = "RUST_CC_MIN_BYTES_THRESHOLD";
+#[cfg(feature = "std")]
+const ENV_MAX_BYTES_THRESHOLD: &str = "RUST_CC_MAX_BYTES_THRESHOLD";
+#[cfg(feature = "std")]
+const ENV_GROWTH_STRATEGY: &str = "RUST_CC_GROWTH_STRATEGY";
+#[cfg(feature = "std")]
+const ENV_MAX_HEAP_BYTES: &str = "RUST_CC_MAX_HEAP_BYTES";
+#[cfg(feature = "std")]
+const ENV_OUT_OF_MEMORY_POLICY: &str = "RUST_CC_OUT_OF_MEMORY_POLICY";
+#[cfg(feature = "std")]
+const ENV_MAX_PAUSE_MICROS: &str = "RUST_CC_MAX_PAUSE_MICROS";
+#[cfg(feature = "std")]
+const ENV_DEFERRED_COLLECTION: &str = "RUST_CC_DEFERRED_COLLECTION";
+#[cfg(all(feature = "std", feature = "finalization"))]
+const ENV_FINALIZATION_MAX_ROUNDS: &str = "RUST_CC_FINALIZATION_MAX_ROUNDS";
+#[cfg(all(feature = "std", feature = "finalization"))]
+const ENV_FINALIZE_CREATED_OBJECTS: &str = "RUST_CC_FINALIZE_CREATED_OBJECTS";
+#[cfg(all(feature = "std", feature = "finalization"))]
+const ENV_LEFTOVER_OBJECTS: &str = "RUST_CC_LEFTOVER_OBJECTS";
+
+/// Returns the default configuration modified by the environment variables.
+///
+/// See the [module-level documentation][`mod@crate::config`] for the list of the supported variables.
+///
+/// # Example
+/// ```rust
+///# use rust_cc::config::*;
+/// std::env::set_var("RUST_CC_BUFFERED_THRESHOLD", "100");
+///
+/// let config = from_env().unwrap();
+/// assert_eq!(100, config.buffered_objects_threshold().unwrap().get());
+///# std::env::remove_var("RUST_CC_BUFFERED_THRESHOLD");
+/// ```
+#[cfg(feature = "std")]
+pub fn from_env() -> Result<Config, EnvConfigError> {
+    let mut config = Config::new();
+    apply_env(&mut config)?;
+    Ok(config)
+}
+
+/// Returns the error which prevented the environment variables from being applied to the configuration of the current
+/// thread, or [`None`] if they have been applied successfully.
+///
+/// In case of error, the configuration is left as if no environment variable was set.
+///
+/// See the [module-level documentation][`mod@crate::config`] for more details.
+#[cfg(feature = "env-config")]
+pub fn env_error() -> Option<EnvConfigError> {
+    // Make sure the environment variables have been applied
+    let _ = config(|_| {});
+    ENV_ERROR.try_with(|error| error.borrow().clone()).ok().flatten()
+}
+
+/// Sets the parameters of `config` which have a corresponding environment variable.
+/// `config` is left untouched if an error is returned.
+#[cfg(feature = "std")]
+fn apply_env(config: &mut Config) -> Result<(), EnvConfigError> {
+    fn var(variable: &'static str) -> Result<Option<String>, EnvConfigError> {
+        match std::env::var_os(variable) {
+            None => Ok(None),
+            Some(value) => value.into_string().map(Some).map_err(|value| EnvConfigError::Malformed {
+                variable,
+                value: value.to_string_lossy().into_owned(),
+                expected: "a valid unicode string",
+            }),
+        }
+    }
+
+    fn parse<T: core::str::FromStr>(variable: &'static str, expected: &'static str) -> Result<Option<T>, EnvConfigError> {
+        var(variable)?.map(|value| {
+            value.trim().parse().map_err(|_| EnvConfigError::Malformed { variable, value, expected })
+        }).transpose()
+    }
+
+    fn parse_optional<T: core::str::FromStr>(variable: &'static str) -> Result<Option<Option<T>>, EnvConfigError> {
+        parse_with(variable, "a non-negative integer or none", |value| match value {
+            "none" => Some(None),
+            value => value.parse().ok().map(Some),
+        })
+    }
+
+    fn parse_with<T>(
+        variable: &'static str,
+        expected: &'static str,
+        f: impl FnOnce(&str) -> Option<T>,
+    ) -> Result<Option<T>, EnvConfigError> {
+        var(variable)?.map(|value| {
+            f(value.trim()).ok_or(EnvConfigError::Malformed { variable, value, expected })
+        }).transpose()
+    }
+
+    let mut new = config.clone();
+    let threshold = &mut new.threshold;
+
+    if let Some(auto_collect) = parse(ENV_AUTO_COLLECT, "true or false")? {
+        new.auto_collect = auto_collect;
+    }
+    if let Some(bytes_threshold) = parse(ENV_BYTES_THRESHOLD, "a non-negative integer")? {
+        threshold.bytes_threshold = bytes_threshold;
+    }
+    if let Some(percent) = parse(ENV_ADJUSTMENT_PERCENT, "a number between 0 and 1")? {
+        threshold.adjustment_percent = percent;
+    }
+    if let Some(buffered_threshold) = parse_optional(ENV_BUFFERED_THRESHOLD)? {
+        threshold.buffered_threshold = buffered_threshold.and_then(NonZeroUsize::new);
+    }
+    if let Some(min) = parse(ENV_MIN_BYTES_THRESHOLD, "a positive integer")? {
+        threshold.min_bytes_threshold = min;
+    }
+    if let Some(max) = parse(ENV_MAX_BYTES_THRESHOLD, "a positive integer")? {
+        threshold.max_bytes_threshold = max;
+    }
+    let growth_strategy = parse_with(ENV_GROWTH_STRATEGY, "doubling or proportional:<percent>", |value| match value {
+        "doubling" => Some(GrowthStrategy::Doubling),
+        value => {
+            let percent = value.strip_prefix("proportional:")?.parse().ok()?;
+            Some(GrowthStrategy::Proportional { percent })
+        },
+    })?;
+    if let Some(growth_strategy) = growth_strategy {
+        threshold.growth_strategy = growth_strategy;
+    }
+    if let Some(limit) = parse_optional(ENV_MAX_HEAP_BYTES)? {
+        new.max_heap_bytes = limit;
+    }
+    let out_of_memory_policy = parse_with(ENV_OUT_OF_MEMORY_POLICY, "panic or error", |value| match value {
+        "panic" => Some(OutOfMemoryPolicy::Panic),
+        "error" => Some(OutOfMemoryPolicy::Error),
+        _ => None,
+    })?;
+    if let Some(out_of_memory_policy) = out_of_memory_policy {
+        new.out_of_memory_policy = out_of_memory_policy;
+    }
+    if let Some(max_pause) = parse_optional(ENV_MAX_PAUSE_MICROS)? {
+        new.max_pause = max_pause.map(Duration::from_micros);
+    }
+    let deferred_collection = parse_with(ENV_DEFERRED_COLLECTION, "run-immediately or wait-for-next-trigger", |value| match value {
+        "run-immediately" => Some(DeferredCollection::RunImmediately),
+        "wait-for-next-trigger" => Some(DeferredCollection::WaitForNextTrigger),
+        _ => None,
+    })?;
+    if let Some(deferred_collection) = deferred_collection {
+        new.deferred_collection = deferred_collection;
+    }
+
+    #[cfg(feature = "finalization")]
+    {
+        let finalization_policy = &mut new.finalization_policy;
+
+        let max_rounds = parse_with(ENV_FINALIZATION_MAX_ROUNDS, "a positive integer", |value| {
+            value.parse().ok().filter(|&rounds| rounds != 0)
+        })?;
+        if let Some(max_rounds) = max_rounds {
+            finalization_policy.max_rounds = max_rounds;
+        }
+        if let Some(finalize_created_objects) = parse(ENV_FINALIZE_CREATED_OBJECTS, "true or false")? {
+            finalization_policy.finalize_created_objects = finalize_created_objects;
+        }
+        let leftover_objects = parse_with(ENV_LEFTOVER_OBJECTS, "keep or drop-without-finalizing", |value| match value {
+            "keep" => Some(LeftoverObjects::Keep),
+            "drop-without-finalizing" => Some(LeftoverObjects::DropWithoutFinalizing),
+            _ => None,
+        })?;
+        if let Some(leftover_objects) = leftover_objects {
+            finalization_policy.leftover_objects = leftover_objects;
+        }
+    }
+
+    new.validate()?;
+    *config = new;
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn apply_default(config: &mut Config) {
+    let _ = APPLIED_GENERATION.try_with(|applied| {
+        if applied.get() == Some(GENERATION.load(Ordering::Acquire)) {
+            return; // Fast path, nothing has changed
+        }
+
+        utils::cold();
+
+        let default = DEFAULT_CONFIG.read().unwrap_or_else(PoisonError::into_inner);
+        if applied.get() != Some(default.generation) {
+            if let Some(shared) = &default.config {
+                config.apply_shared(shared);
+            }
+
+            // The environment variables are applied on top of the default configuration on first access.
+            // Panicking here would make every configuration access panic, so just save the error for env_error
+            #[cfg(feature = "env-config")]
+            if applied.get().is_none() {
+                let error = apply_env(config).err();
+                let _ = ENV_ERROR.try_with(|env_error| *env_error.borrow_mut() = error);
+            }
+
+            applied.set(Some(default.generation));
+        }
+    });
+}
+
 /// An error returned by [`config`][`fn@config`].
 #[non_exhaustive]
 #[derive(Error, Debug)]
@@ -79,11 +535,15 @@ pub enum ConfigAccessError {
 /// The configuration of the garbage collector.
 #[derive(Debug, Clone)]
 pub struct Config {
-    // The invariant is:
-    // bytes_threshold * adjustment_percent < allocated_bytes < bytes_threshold
-    bytes_threshold: usize,
-    adjustment_percent: f64,
-    buffered_threshold: Option<NonZeroUsize>,
+    threshold: ThresholdPolicy,
+    policy: Option<Box<dyn CollectionPolicy>>, // If None, threshold is used
+    max_heap_bytes: Option<usize>,
+    out_of_memory_policy: OutOfMemoryPolicy,
+    #[cfg(feature = "std")]
+    max_pause: Option<Duration>,
+    #[cfg(feature = "finalization")]
+    finalization_policy: FinalizationPolicy,
+    deferred_collection: DeferredCollection,
     auto_collect: bool,
     _phantom: PhantomData<Rc<()>>, // Make Config !Send and !Sync
 }
@@ -92,14 +552,28 @@ impl Config {
     #[inline]
     const fn new() -> Self {
         Self {
-            bytes_threshold: DEFAULT_BYTES_THRESHOLD,
-            adjustment_percent: 0.1,
-            buffered_threshold: None,
+            threshold: ThresholdPolicy::new(),
+            policy: None,
+            max_heap_bytes: None,
+            out_of_memory_policy: OutOfMemoryPolicy::Panic,
+            #[cfg(feature = "std")]
+            max_pause: None,
+            #[cfg(feature = "finalization")]
+            finalization_policy: FinalizationPolicy::new(),
+            deferred_collection: DeferredCollection::RunImmediately,
             auto_collect: true,
             _phantom: PhantomData,
         }
     }
 
+    /// Returns a [`ConfigBuilder`], which can be used to create a validated configuration.
+    ///
+    /// See [`ConfigBuilder`] for more details.
+    #[inline]
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder { config: Config::new() }
+    }
+
     /// Returns `true` if collections can be automatically started, `false` otherwise.
     #[inline]
     pub fn auto_collect(&self) -> bool {
@@ -117,7 +591,7 @@ pub fn set_auto_collect(&mut self, auto_collect: bool) {
     /// See the [module-level documentation][`mod@crate::config`] for more details.
     #[inline]
     pub fn adjustment_percent(&self) -> f64 {
-        self.adjustment_percent
+        self.threshold.adjustment_percent
     }
 
     /// Sets the threshold adjustment percent.
@@ -134,7 +608,7 @@ pub fn set_adjustment_percent(&mut self, percent: f64) {
             (0f64..=1f64).contains(&percent),
             "percent must be between 0 and 1"
         );
-        self.adjustment_percent = percent;
+        self.threshold.adjustment_percent = percent;
     }
 
     /// Returns the buffered-objects threshold (see [`Cc::mark_alive`][`crate::Cc::mark_alive`]).
@@ -144,7 +618,7 @@ pub fn set_adjustment_percent(&mut self, percent: f64) {
     /// See the [module-level documentation][`mod@crate::config`] for more details.
     #[inline]
     pub fn buffered_objects_threshold(&self) -> Option<NonZeroUsize> {
-        self.buffered_threshold
+        self.threshold.buffered_threshold
     }
 
     /// Sets the buffered-objects threshold (see [`Cc::mark_alive`][`crate::Cc::mark_alive`]).
@@ -155,35 +629,826 @@ pub fn buffered_objects_threshold(&self) -> Option<NonZeroUsize> {
     #[inline]
     #[track_caller]
     pub fn set_buffered_objects_threshold(&mut self, threshold: Option<NonZeroUsize>) {
-        self.buffered_threshold = threshold;
+        self.threshold.buffered_threshold = threshold;
+    }
+
+    /// Returns the strategy used to adjust the threshold after a collection.
+    ///
+    /// See the [module-level documentation][`mod@crate::config`] for more details.
+    #[inline]
+    pub fn growth_strategy(&self) -> GrowthStrategy {
+        self.threshold.growth_strategy
+    }
+
+    /// Sets the strategy used to adjust the threshold after a collection.
+    ///
+    /// See the [module-level documentation][`mod@crate::config`] for more details.
+    #[inline]
+    pub fn set_growth_strategy(&mut self, strategy: GrowthStrategy) {
+        self.threshold.growth_strategy = strategy;
+    }
+
+    /// Returns the minimum value of the threshold.
+    ///
+    /// See the [module-level documentation][`mod@crate::config`] for more details.
+    #[inline]
+    pub fn min_bytes_threshold(&self) -> usize {
+        self.threshold.min_bytes_threshold
+    }
+
+    /// Sets the minimum value of the threshold.
+    ///
+    /// See the [module-level documentation][`mod@crate::config`] for more details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided `threshold` is 0 or is greater than the [`max_bytes_threshold`][`fn@Config::max_bytes_threshold`].
+    #[inline]
+    #[track_caller]
+    pub fn set_min_bytes_threshold(&mut self, threshold: usize) {
+        assert!(threshold != 0, "the minimum threshold must be greater than 0");
+        assert!(
+            threshold <= self.threshold.max_bytes_threshold,
+            "the minimum threshold must not be greater than the maximum threshold"
+        );
+        self.threshold.min_bytes_threshold = threshold;
+        self.threshold.clamp_bytes_threshold();
+    }
+
+    /// Returns the maximum value of the threshold.
+    ///
+    /// See the [module-level documentation][`mod@crate::config`] for more details.
+    #[inline]
+    pub fn max_bytes_threshold(&self) -> usize {
+        self.threshold.max_bytes_threshold
+    }
+
+    /// Sets the maximum value of the threshold.
+    ///
+    /// Note that when the bytes alive after a collection exceed the maximum, a collection is started
+    /// every time a collection may be automatically started.
+    ///
+    /// See the [module-level documentation][`mod@crate::config`] for more details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided `threshold` is lower than the [`min_bytes_threshold`][`fn@Config::min_bytes_threshold`].
+    #[inline]
+    #[track_caller]
+    pub fn set_max_bytes_threshold(&mut self, threshold: usize) {
+        assert!(
+            threshold >= self.threshold.min_bytes_threshold,
+            "the maximum threshold must not be lower than the minimum threshold"
+        );
+        self.threshold.max_bytes_threshold = threshold;
+        self.threshold.clamp_bytes_threshold();
+    }
+
+    /// Returns the maximum number of bytes which can be allocated, or [`None`] if there's no limit.
+    ///
+    /// See the [module-level documentation][`mod@crate::config`] for more details.
+    #[inline]
+    pub fn max_heap_bytes(&self) -> Option<usize> {
+        self.max_heap_bytes
+    }
+
+    /// Sets the maximum number of bytes which can be allocated. Passing [`None`] removes the limit.
+    ///
+    /// The limit includes the external bytes (see [`report_external_allocation`][`crate::state::report_external_allocation`]).
+    ///
+    /// Setting a limit lower than the currently allocated bytes doesn't free any memory, but makes
+    /// every new allocation fail until enough memory is freed.
+    ///
+    /// Note that the limit is enforced even if [`auto_collect`][`fn@Config::auto_collect`] is `false`.
+    ///
+    /// See the [module-level documentation][`mod@crate::config`] for more details.
+    #[inline]
+    pub fn set_max_heap_bytes(&mut self, limit: Option<usize>) {
+        self.max_heap_bytes = limit;
+    }
+
+    /// Returns the policy applied when an allocation doesn't fit into the [`max_heap_bytes`][`fn@Config::max_heap_bytes`].
+    ///
+    /// See the [module-level documentation][`mod@crate::config`] for more details.
+    #[inline]
+    pub fn out_of_memory_policy(&self) -> OutOfMemoryPolicy {
+        self.out_of_memory_policy
+    }
+
+    /// Sets the policy applied when an allocation doesn't fit into the [`max_heap_bytes`][`fn@Config::max_heap_bytes`].
+    ///
+    /// See the [module-level documentation][`mod@crate::config`] for more details.
+    #[inline]
+    pub fn set_out_of_memory_policy(&mut self, policy: OutOfMemoryPolicy) {
+        self.out_of_memory_policy = policy;
+    }
+
+    /// Returns the maximum pause of automatically started collections, or [`None`] if they aren't limited.
+    ///
+    /// See the [module-level documentation][`mod@crate::config`] for more details.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn max_pause(&self) -> Option<Duration> {
+        self.max_pause
+    }
+
+    /// Sets the maximum pause of automatically started collections. Passing [`None`] removes the limit.
+    ///
+    /// Collections started by [`collect_cycles`][`crate::collect_cycles`], by the [heap limit](#heap-limit) or at thread
+    /// exit are never limited.
+    ///
+    /// See the [module-level documentation][`mod@crate::config`] for more details.
+    ///
+    /// # Example
+    /// ```rust
+    ///# use rust_cc::config::*;
+    ///# use std::time::Duration;
+    /// config(|config| config.set_max_pause(Some(Duration::from_millis(1)))).unwrap();
+    ///# config(|config| config.set_max_pause(None)).unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn set_max_pause(&mut self, max_pause: Option<Duration>) {
+        self.max_pause = max_pause;
+    }
+
+    /// Returns what happens to a collection which became due while automatic collections were suppressed
+    /// using [`no_collect`][`crate::no_collect`].
+    #[inline]
+    pub fn deferred_collection(&self) -> DeferredCollection {
+        self.deferred_collection
+    }
+
+    /// Sets what happens to a collection which became due while automatic collections were suppressed
+    /// using [`no_collect`][`crate::no_collect`].
+    #[inline]
+    pub fn set_deferred_collection(&mut self, deferred_collection: DeferredCollection) {
+        self.deferred_collection = deferred_collection;
+    }
+
+    /// Returns the [`FinalizationPolicy`].
+    ///
+    /// See the [module-level documentation][`mod@crate::config`] for more details.
+    #[cfg(feature = "finalization")]
+    #[inline]
+    pub fn finalization_policy(&self) -> FinalizationPolicy {
+        self.finalization_policy
+    }
+
+    /// Sets the [`FinalizationPolicy`].
+    ///
+    /// See the [module-level documentation][`mod@crate::config`] for more details.
+    ///
+    /// # Example
+    /// ```rust
+    ///# use rust_cc::config::*;
+    /// let mut policy = FinalizationPolicy::default();
+    /// policy.set_max_rounds(20);
+    /// policy.set_finalize_created_objects(true);
+    /// policy.set_leftover_objects(LeftoverObjects::DropWithoutFinalizing);
+    ///
+    /// config(|config| config.set_finalization_policy(policy)).unwrap();
+    ///# config(|config| config.set_finalization_policy(FinalizationPolicy::default())).unwrap();
+    /// ```
+    #[cfg(feature = "finalization")]
+    #[inline]
+    pub fn set_finalization_policy(&mut self, policy: FinalizationPolicy) {
+        self.finalization_policy = policy;
+    }
+
+    /// Returns a copy of the default [`ThresholdPolicy`], configured using the parameters of this configuration.
+    ///
+    /// This is useful to implement a [`CollectionPolicy`] which extends the default one.
+    #[inline]
+    pub fn threshold_policy(&self) -> ThresholdPolicy {
+        self.threshold.clone()
+    }
+
+    /// Installs a custom [`CollectionPolicy`], which replaces the default [`ThresholdPolicy`].
+    ///
+    /// The [`auto_collect`][`fn@Config::auto_collect`] parameter is still respected.
+    ///
+    /// See the [module-level documentation][`mod@crate::config`] for more details.
+    ///
+    /// # Example
+    /// ```rust
+    ///# use rust_cc::config::*;
+    /// #[derive(Debug, Clone)]
+    /// struct EveryThousandAllocations;
+    ///
+    /// impl CollectionPolicy for EveryThousandAllocations {
+    ///     fn should_collect(&mut self, ctx: &PolicyContext<'_>) -> bool {
+    ///         ctx.allocations_since_last_collection() >= 1000
+    ///     }
+    /// }
+    ///
+    /// config(|config| config.set_policy(Box::new(EveryThousandAllocations))).unwrap();
+    ///# config(|config| config.take_policy()).unwrap();
+    /// ```
+    #[inline]
+    pub fn set_policy(&mut self, policy: Box<dyn CollectionPolicy>) {
+        self.policy = Some(policy);
+    }
+
+    /// Removes the custom [`CollectionPolicy`] installed using [`set_policy`][`fn@Config::set_policy`], if any,
+    /// restoring the default [`ThresholdPolicy`].
+    #[inline]
+    pub fn take_policy(&mut self) -> Option<Box<dyn CollectionPolicy>> {
+        self.policy.take()
     }
 
     #[inline(always)]
-    pub(super) fn should_collect(&mut self, state: &State, possible_cycles: &RefCell<CountedList>) -> bool {
+    pub(super) fn should_collect(&mut self, state: &State, possible_cycles: &RefCell<ManuallyDrop<CountedList>>) -> Option<CollectionTrigger> {
         if !self.auto_collect {
-            return false;
+            return None;
         }
 
-        if state.allocated_bytes() > self.bytes_threshold {
-            return true;
+        #[cfg(feature = "std")]
+        if state.is_collection_interrupted() {
+            return Some(CollectionTrigger::Resumed);
         }
 
-        return if let Some(buffered_threshold) = self.buffered_threshold {
-            possible_cycles.try_borrow().map_or(false, |pc| pc.size() > buffered_threshold.get())
-        } else {
-            false
+        match &mut self.policy {
+            None => self.threshold.should_collect_trigger(state, possible_cycles),
+            Some(policy) => {
+                utils::cold();
+                policy.should_collect(&PolicyContext::new(state, possible_cycles)).then_some(CollectionTrigger::Policy)
+            },
         }
     }
 
     #[inline(always)]
-    pub(super) fn adjust(&mut self, state: &State) {
+    pub(super) fn adjust(&mut self, state: &State, possible_cycles: &RefCell<ManuallyDrop<CountedList>>) {
+        let ctx = PolicyContext::new(state, possible_cycles);
+        match &mut self.policy {
+            None => self.threshold.adjust(&ctx),
+            Some(policy) => policy.adjust(&ctx),
+        }
+    }
+
+    /// Validates the parameters which have been set without the checks done by the setters.
+    fn validate(&mut self) -> Result<(), InvalidConfigError> {
+        #[cfg(feature = "finalization")]
+        if self.finalization_policy.max_rounds == 0 {
+            return Err(InvalidConfigError::ZeroFinalizationRounds);
+        }
+
+        self.threshold.validate()
+    }
+}
+
+/// A builder for [`Config`], which validates the parameters instead of panicking.
+///
+/// Parameters which aren't set keep their default value. See [`Config`] for the meaning of every parameter.
+///
+/// # Example
+/// ```rust
+///# use rust_cc::config::*;
+/// let config = Config::builder()
+///     .adjustment_percent(0.2)
+///     .min_bytes_threshold(1024)
+///     .build()
+///     .unwrap();
+/// assert_eq!(1024, config.min_bytes_threshold());
+///
+/// assert!(Config::builder().adjustment_percent(2.0).build().is_err());
+/// ```
+#[derive(Debug, Clone)]
+#[must_use = "the builder does nothing until build is called"]
+pub struct ConfigBuilder {
+    config: Config, // Parameters are set without validation, build validates them
+}
+
+impl ConfigBuilder {
+    /// See [`Config::set_auto_collect`].
+    #[inline]
+    pub fn auto_collect(mut self, auto_collect: bool) -> Self {
+        self.config.auto_collect = auto_collect;
+        self
+    }
+
+    /// See [`Config::set_adjustment_percent`].
+    #[inline]
+    pub fn adjustment_percent(mut self, percent: f64) -> Self {
+        self.config.threshold.adjustment_percent = percent;
+        self
+    }
+
+    /// See [`Config::set_buffered_objects_threshold`].
+    #[inline]
+    pub fn buffered_objects_threshold(mut self, threshold: Option<NonZeroUsize>) -> Self {
+        self.config.threshold.buffered_threshold = threshold;
+        self
+    }
+
+    /// See [`Config::set_growth_strategy`].
+    #[inline]
+    pub fn growth_strategy(mut self, strategy: GrowthStrategy) -> Self {
+        self.config.threshold.growth_strategy = strategy;
+        self
+    }
+
+    /// See [`Config::set_min_bytes_threshold`].
+    #[inline]
+    pub fn min_bytes_threshold(mut self, threshold: usize) -> Self {
+        self.config.threshold.min_bytes_threshold = threshold;
+        self
+    }
+
+    /// See [`Config::set_max_bytes_threshold`].
+    #[inline]
+    pub fn max_bytes_threshold(mut self, threshold: usize) -> Self {
+        self.config.threshold.max_bytes_threshold = threshold;
+        self
+    }
+
+    /// See [`Config::set_max_heap_bytes`].
+    #[inline]
+    pub fn max_heap_bytes(mut self, limit: Option<usize>) -> Self {
+        self.config.max_heap_bytes = limit;
+        self
+    }
+
+    /// See [`Config::set_out_of_memory_policy`].
+    #[inline]
+    pub fn out_of_memory_policy(mut self, policy: OutOfMemoryPolicy) -> Self {
+        self.config.out_of_memory_policy = policy;
+        self
+    }
+
+    /// See [`Config::set_max_pause`].
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn max_pause(mut self, max_pause: Option<Duration>) -> Self {
+        self.config.max_pause = max_pause;
+        self
+    }
+
+    /// See [`Config::set_finalization_policy`].
+    #[cfg(feature = "finalization")]
+    #[inline]
+    pub fn finalization_policy(mut self, policy: FinalizationPolicy) -> Self {
+        self.config.finalization_policy = policy;
+        self
+    }
+
+    /// See [`Config::set_deferred_collection`].
+    #[inline]
+    pub fn deferred_collection(mut self, deferred_collection: DeferredCollection) -> Self {
+        self.config.deferred_collection = deferred_collection;
+        self
+    }
+
+    /// See [`Config::set_policy`].
+    #[inline]
+    pub fn policy(mut self, policy: Box<dyn CollectionPolicy>) -> Self {
+        self.config.policy = Some(policy);
+        self
+    }
+
+    /// Validates the parameters and builds the [`Config`].
+    pub fn build(self) -> Result<Config, InvalidConfigError> {
+        let mut config = self.config;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// What happens to a collection which became due while automatic collections were suppressed
+/// using [`no_collect`][`crate::no_collect`] or a [`NoCollectGuard`][`crate::NoCollectGuard`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeferredCollection {
+    /// Run the collection as soon as the outermost suppression ends.
+    #[default]
+    RunImmediately,
+    /// Leave the collection to the next time a collection may be automatically started (e.g. the next [`Cc::new`][`crate::Cc::new`]).
+    WaitForNextTrigger,
+}
+
+/// An error returned by [`ConfigBuilder::build`] when a parameter is invalid.
+#[non_exhaustive]
+#[derive(Error, Debug, Clone, Copy, PartialEq)]
+pub enum InvalidConfigError {
+    /// The adjustment percent isn't between 0 and 1 (included).
+    #[error("percent must be between 0 and 1, got {0}")]
+    AdjustmentPercent(f64),
+    /// The minimum threshold is 0.
+    #[error("the minimum threshold must be greater than 0")]
+    ZeroMinBytesThreshold,
+    /// The minimum threshold is greater than the maximum threshold.
+    #[error("the minimum threshold ({min}) must not be greater than the maximum threshold ({max})")]
+    BytesThresholdBounds {
+        /// The minimum threshold.
+        min: usize,
+        /// The maximum threshold.
+        max: usize,
+    },
+    /// The maximum number of finalization rounds is 0.
+    #[cfg(feature = "finalization")]
+    #[error("the maximum number of rounds must be greater than 0")]
+    ZeroFinalizationRounds,
+}
+
+/// An error returned by [`from_env`][`fn@from_env`].
+#[cfg(feature = "std")]
+#[non_exhaustive]
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum EnvConfigError {
+    /// The value of an environment variable couldn't be parsed.
+    #[error("malformed value {value:?} for {variable}, expected {expected}")]
+    Malformed {
+        /// The name of the environment variable.
+        variable: &'static str,
+        /// The malformed value.
+        value: String,
+        /// A description of the expected values.
+        expected: &'static str,
+    },
+    /// The configuration described by the environment variables isn't valid.
+    #[error(transparent)]
+    Invalid(#[from] InvalidConfigError),
+}
+
+/// What to do when a new allocation doesn't fit into the [`max_heap_bytes`][`fn@Config::max_heap_bytes`],
+/// even after a collection.
+///
+/// See the [module-level documentation][`mod@crate::config`] for more details.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default)]
+pub enum OutOfMemoryPolicy {
+    /// Panic, even when using [`Cc::try_new`][`crate::Cc::try_new`].
+    #[default]
+    Panic,
+    /// Call the provided function, which may free some memory (for example, by clearing caches).
+    ///
+    /// If the allocation still doesn't fit after the function returns, [`Cc::new`][`crate::Cc::new`] panics and
+    /// [`Cc::try_new`][`crate::Cc::try_new`] returns an error.
+    Callback(fn(HeapLimitError)),
+    /// Make [`Cc::try_new`][`crate::Cc::try_new`] return an error. [`Cc::new`][`crate::Cc::new`] panics instead.
+    Error,
+}
+
+/// The error returned when a new allocation doesn't fit into the [`max_heap_bytes`][`fn@Config::max_heap_bytes`].
+///
+/// See the [module-level documentation][`mod@crate::config`] for more details.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("allocating {requested} bytes would exceed the heap limit ({allocated} bytes allocated, limit is {limit} bytes)")]
+pub struct HeapLimitError {
+    requested: usize,
+    allocated: usize,
+    limit: usize,
+}
+
+impl HeapLimitError {
+    #[inline]
+    pub(crate) fn new(requested: usize, allocated: usize, limit: usize) -> Self {
+        Self { requested, allocated, limit }
+    }
+
+    /// Returns the number of bytes of the failed allocation.
+    #[inline]
+    pub fn requested(&self) -> usize {
+        self.requested
+    }
+
+    /// Returns the number of bytes allocated when the allocation failed, including the external bytes.
+    #[inline]
+    pub fn allocated(&self) -> usize {
+        self.allocated
+    }
+
+    /// Returns the [`max_heap_bytes`][`fn@Config::max_heap_bytes`] when the allocation failed.
+    #[inline]
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+/// How finalization is handled by collections.
+///
+/// See the [module-level documentation][`mod@crate::config`] for more details.
+#[cfg(feature = "finalization")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FinalizationPolicy {
+    max_rounds: usize,
+    finalize_created_objects: bool,
+    leftover_objects: LeftoverObjects,
+}
+
+#[cfg(feature = "finalization")]
+impl FinalizationPolicy {
+    #[inline]
+    const fn new() -> Self {
+        Self {
+            max_rounds: crate::DEFAULT_FINALIZATION_ROUNDS,
+            finalize_created_objects: false,
+            leftover_objects: LeftoverObjects::Keep,
+        }
+    }
+
+    /// Returns the maximum number of times the collection algorithm is executed by a single collection (10 by default).
+    ///
+    /// A collection usually completes in 2 rounds. Reaching the maximum almost surely means that
+    /// some finalizer keeps creating new garbage, for example by dropping one object at a time.
+    #[inline]
+    pub fn max_rounds(&self) -> usize {
+        self.max_rounds
+    }
+
+    /// Sets the maximum number of times the collection algorithm is executed by a single collection.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided `rounds` is 0.
+    #[inline]
+    #[track_caller]
+    pub fn set_max_rounds(&mut self, rounds: usize) {
+        assert!(rounds != 0, "the maximum number of rounds must be greater than 0");
+        self.max_rounds = rounds;
+    }
+
+    /// Returns whether the objects created during finalization are finalized when they become garbage (`false` by default).
+    ///
+    /// When `false`, the objects created inside [`Finalize::finalize`][`crate::Finalize::finalize`] are
+    /// considered already finalized, so they are never finalized unless [`Cc::finalize_again`][`crate::Cc::finalize_again`]
+    /// is called on them. This prevents finalizers from keeping a collection busy by creating new objects to finalize.
+    #[inline]
+    pub fn finalize_created_objects(&self) -> bool {
+        self.finalize_created_objects
+    }
+
+    /// Sets whether the objects created during finalization are finalized when they become garbage.
+    #[inline]
+    pub fn set_finalize_created_objects(&mut self, finalize: bool) {
+        self.finalize_created_objects = finalize;
+    }
+
+    /// Returns what to do with the objects still to process after the last round.
+    #[inline]
+    pub fn leftover_objects(&self) -> LeftoverObjects {
+        self.leftover_objects
+    }
+
+    /// Sets what to do with the objects still to process after the last round.
+    #[inline]
+    pub fn set_leftover_objects(&mut self, leftover_objects: LeftoverObjects) {
+        self.leftover_objects = leftover_objects;
+    }
+}
+
+#[cfg(feature = "finalization")]
+impl Default for FinalizationPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What to do with the objects still to process after the last round of a collection.
+///
+/// See [`FinalizationPolicy::max_rounds`] for more details.
+#[cfg(feature = "finalization")]
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LeftoverObjects {
+    /// Leave the objects to the next collection.
+    #[default]
+    Keep,
+    /// Execute one more round of the collection algorithm, dropping the garbage objects without finalizing them.
+    DropWithoutFinalizing,
+}
+
+/// A policy deciding when collections are automatically started.
+///
+/// A policy can be installed using [`Config::set_policy`]. It is asked whether to start a collection every time
+/// a collection may be automatically started (e.g. when calling [`Cc::new`][`crate::Cc::new`]) and it is notified
+/// at the end of every collection, so that it can adapt its parameters.
+///
+/// Policies are cloned together with their [`Config`], for example when using [`set_default`][`fn@set_default`].
+pub trait CollectionPolicy: CollectionPolicyClone + Debug + Send + Sync + 'static {
+    /// Returns whether a collection should be started.
+    ///
+    /// Collections started because this method returned `true` have [`CollectionTrigger::Policy`] as trigger.
+    fn should_collect(&mut self, ctx: &PolicyContext<'_>) -> bool;
+
+    /// Called at the end of every collection, including the ones not started by this policy.
+    ///
+    /// The default implementation does nothing.
+    #[inline]
+    fn adjust(&mut self, ctx: &PolicyContext<'_>) {
+        let _ = ctx;
+    }
+}
+
+/// Implementation detail of [`CollectionPolicy`], automatically implemented for every [`Clone`] policy.
+pub trait CollectionPolicyClone {
+    /// Returns a boxed clone of this policy.
+    fn clone_policy(&self) -> Box<dyn CollectionPolicy>;
+}
+
+impl<T: CollectionPolicy + Clone> CollectionPolicyClone for T {
+    #[inline]
+    fn clone_policy(&self) -> Box<dyn CollectionPolicy> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn CollectionPolicy> {
+    #[inline]
+    fn clone(&self) -> Self {
+        self.clone_policy()
+    }
+}
+
+/// The state of the collector, passed to a [`CollectionPolicy`].
+pub struct PolicyContext<'a> {
+    state: &'a State,
+    possible_cycles: &'a RefCell<ManuallyDrop<CountedList>>,
+}
+
+impl<'a> PolicyContext<'a> {
+    #[inline]
+    fn new(state: &'a State, possible_cycles: &'a RefCell<ManuallyDrop<CountedList>>) -> Self {
+        Self { state, possible_cycles }
+    }
+
+    /// Returns the number of bytes currently allocated by the collector.
+    ///
+    /// See [`allocated_bytes`][`fn@crate::state::allocated_bytes`] for more details.
+    #[inline]
+    pub fn allocated_bytes(&self) -> usize {
+        self.state.allocated_bytes()
+    }
+
+    /// Returns the number of objects buffered to be processed in the next collection, or [`None`] if it isn't available.
+    ///
+    /// See [`buffered_objects_count`][`fn@crate::state::buffered_objects_count`] for more details.
+    #[inline]
+    pub fn buffered_objects(&self) -> Option<usize> {
+        self.possible_cycles.try_borrow().ok().map(|pc| pc.size())
+    }
+
+    /// Returns the number of bytes of external memory reported using
+    /// [`report_external_allocation`][`fn@crate::state::report_external_allocation`].
+    #[inline]
+    pub fn external_bytes(&self) -> usize {
+        self.state.external_bytes()
+    }
+
+    /// Returns the sum of [`allocated_bytes`][`PolicyContext::allocated_bytes`] and [`external_bytes`][`PolicyContext::external_bytes`].
+    #[inline]
+    pub fn memory_pressure(&self) -> usize {
+        self.state.memory_pressure()
+    }
+
+    /// Returns the number of bytes found alive by the last collection, or 0 if no collection has been executed yet.
+    ///
+    /// See [`live_bytes_after_last_collection`][`fn@crate::state::live_bytes_after_last_collection`] for more details.
+    #[inline]
+    pub fn live_bytes_after_last_collection(&self) -> usize {
+        self.state.live_bytes_after_collection()
+    }
+
+    /// Returns the number of objects allocated since the end of the last collection.
+    ///
+    /// Objects allocated by finalizers and destructors during a collection are not counted.
+    #[inline]
+    pub fn allocations_since_last_collection(&self) -> usize {
+        self.state.allocations_since_last_collection()
+    }
+
+    /// Returns the time passed since the end of the last collection, or [`None`] if no collection has been executed yet.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn time_since_last_collection(&self) -> Option<Duration> {
+        self.state.last_collection_end().map(|end| end.elapsed())
+    }
+
+    /// Returns the report of the last collection, or [`None`] if no collection has been executed yet.
+    #[inline]
+    pub fn last_report(&self) -> Option<CollectionReport> {
+        self.state.last_report()
+    }
+}
+
+/// The default [`CollectionPolicy`], which keeps a threshold over the number of allocated bytes.
+///
+/// Its parameters are set through [`Config`]. See the [module-level documentation][`mod@crate::config`] for more details.
+#[derive(Debug, Clone)]
+pub struct ThresholdPolicy {
+    // When using GrowthStrategy::Doubling the invariant is:
+    // bytes_threshold * adjustment_percent < allocated_bytes < bytes_threshold
+    bytes_threshold: usize,
+    adjustment_percent: f64,
+    buffered_threshold: Option<NonZeroUsize>,
+    growth_strategy: GrowthStrategy,
+    min_bytes_threshold: usize,
+    max_bytes_threshold: usize,
+}
+
+/// The strategy used by the [`ThresholdPolicy`] to adjust its threshold after a collection.
+///
+/// See the [module-level documentation][`mod@crate::config`] for more details.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GrowthStrategy {
+    /// The threshold is doubled or halved until the allocated bytes are between the threshold and
+    /// the threshold multiplied by the [`adjustment_percent`][`fn@Config::adjustment_percent`].
+    #[default]
+    Doubling,
+    /// The threshold is set to the bytes alive after the collection increased by the provided percent.
+    Proportional {
+        /// The percent of the bytes alive after a collection which can be allocated before starting the next collection.
+        percent: u32,
+    },
+}
+
+impl ThresholdPolicy {
+    #[inline]
+    const fn new() -> Self {
+        Self {
+            bytes_threshold: DEFAULT_BYTES_THRESHOLD,
+            adjustment_percent: 0.1,
+            buffered_threshold: None,
+            growth_strategy: GrowthStrategy::Doubling,
+            min_bytes_threshold: DEFAULT_BYTES_THRESHOLD,
+            max_bytes_threshold: usize::MAX,
+        }
+    }
+
+    #[inline]
+    fn clamp_bytes_threshold(&mut self) {
+        self.bytes_threshold = self.bytes_threshold.clamp(self.min_bytes_threshold, self.max_bytes_threshold);
+    }
+
+    /// Validates the parameters which have been set without the checks done by the setters of [`Config`].
+    fn validate(&mut self) -> Result<(), InvalidConfigError> {
+        if !(0f64..=1f64).contains(&self.adjustment_percent) {
+            return Err(InvalidConfigError::AdjustmentPercent(self.adjustment_percent));
+        }
+        if self.min_bytes_threshold == 0 {
+            return Err(InvalidConfigError::ZeroMinBytesThreshold);
+        }
+        if self.min_bytes_threshold > self.max_bytes_threshold {
+            return Err(InvalidConfigError::BytesThresholdBounds {
+                min: self.min_bytes_threshold,
+                max: self.max_bytes_threshold,
+            });
+        }
+
+        self.clamp_bytes_threshold();
+        Ok(())
+    }
+
+    /// Like [`should_collect`][`CollectionPolicy::should_collect`], but also returns what started the collection.
+    #[inline(always)]
+    fn should_collect_trigger(&self, state: &State, possible_cycles: &RefCell<ManuallyDrop<CountedList>>) -> Option<CollectionTrigger> {
+        if state.memory_pressure() > self.bytes_threshold {
+            return Some(CollectionTrigger::BytesThreshold);
+        }
+
+        let buffered_threshold = self.buffered_threshold?;
+        possible_cycles.try_borrow()
+            .is_ok_and(|pc| pc.size() > buffered_threshold.get())
+            .then_some(CollectionTrigger::BufferedThreshold)
+    }
+}
+
+impl Default for ThresholdPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CollectionPolicy for ThresholdPolicy {
+    #[inline]
+    fn should_collect(&mut self, ctx: &PolicyContext<'_>) -> bool {
+        self.should_collect_trigger(ctx.state, ctx.possible_cycles).is_some()
+    }
+
+    fn adjust(&mut self, ctx: &PolicyContext<'_>) {
+        match self.growth_strategy {
+            GrowthStrategy::Doubling => self.adjust_doubling(ctx.memory_pressure()),
+            GrowthStrategy::Proportional { percent } => {
+                let live = ctx.live_bytes_after_last_collection() as u128;
+                let threshold = live + live * percent as u128 / 100;
+                self.bytes_threshold = usize::try_from(threshold).unwrap_or(usize::MAX);
+            },
+        }
+        self.clamp_bytes_threshold();
+    }
+}
+
+impl ThresholdPolicy {
+    fn adjust_doubling(&mut self, allocated_bytes: usize) {
         // First case: the threshold might have to be increased
-        if state.allocated_bytes() >= self.bytes_threshold {
+        if allocated_bytes >= self.bytes_threshold {
 
-            loop {
-                let Some(new_threshold) = self.bytes_threshold.checked_shl(1) else { break; };
+            while let Some(new_threshold) = self.bytes_threshold.checked_shl(1) {
                 self.bytes_threshold = new_threshold;
-                if state.allocated_bytes() < self.bytes_threshold {
+                if allocated_bytes < self.bytes_threshold {
                     break;
                 }
             }
@@ -192,7 +1457,7 @@ pub(super) fn adjust(&mut self, state: &State) {
         }
 
         // Second case: the threshold might have to be decreased
-        let allocated = state.allocated_bytes() as f64;
+        let allocated = allocated_bytes as f64;
 
         // If adjustment_percent or the result of the multiplication is 0 do nothing
         if ((self.bytes_threshold as f64) * self.adjustment_percent) == 0.0 {
@@ -202,11 +1467,11 @@ pub(super) fn adjust(&mut self, state: &State) {
         // No more cases after this, there's no need to use an additional if as above
         while allocated <= ((self.bytes_threshold as f64) * self.adjustment_percent) {
             let new_threshold = self.bytes_threshold >> 1;
-            if state.allocated_bytes() >= new_threshold {
+            if allocated_bytes >= new_threshold {
                 break; // If the shift produces a threshold <= allocated, then don't update bytes_threshold to maintain the invariant
             }
-            if new_threshold <= DEFAULT_BYTES_THRESHOLD {
-                self.bytes_threshold = DEFAULT_BYTES_THRESHOLD;
+            if new_threshold <= self.min_bytes_threshold {
+                self.bytes_threshold = self.min_bytes_threshold;
                 break;
             }
             self.bytes_threshold = new_threshold;
@@ -214,6 +1479,116 @@ pub(super) fn adjust(&mut self, state: &State) {
     }
 }
 
+/// The [`Send`] and [`Sync`] part of a [`Config`], used for the process-wide configuration.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+struct SharedConfig {
+    threshold: ThresholdPolicy,
+    policy: Option<Box<dyn CollectionPolicy>>,
+    max_heap_bytes: Option<usize>,
+    out_of_memory_policy: OutOfMemoryPolicy,
+    max_pause: Option<Duration>,
+    #[cfg(feature = "finalization")]
+    finalization_policy: FinalizationPolicy,
+    deferred_collection: DeferredCollection,
+    auto_collect: bool,
+}
+
+#[cfg(feature = "std")]
+impl Config {
+    /// Returns the parameters which can be read by [`from_env`][`fn@from_env`] as environment variables,
+    /// in the `NAME=value` format and separated by newlines.
+    ///
+    /// Reading the returned variables using [`from_env`][`fn@from_env`] produces the same configuration, except for the
+    /// parameters which can't be expressed as environment variables: the [`CollectionPolicy`] set using
+    /// [`set_policy`][`fn@Config::set_policy`], an [`OutOfMemoryPolicy::Callback`] and the fractions of microsecond of
+    /// the [`max_pause`][`fn@Config::max_pause`].
+    ///
+    /// See the [module-level documentation][`mod@crate::config`] for more details.
+    ///
+    /// # Example
+    /// ```rust
+    ///# use rust_cc::config::*;
+    /// let env = Config::default().to_env_string();
+    /// assert!(env.lines().any(|line| line == "RUST_CC_AUTO_COLLECT=true"));
+    /// ```
+    pub fn to_env_string(&self) -> String {
+        fn optional<T: ToString>(value: Option<T>) -> String {
+            value.map_or_else(|| "none".to_string(), |value| value.to_string())
+        }
+
+        let threshold = &self.threshold;
+        let growth_strategy = match threshold.growth_strategy {
+            GrowthStrategy::Doubling => "doubling".to_string(),
+            GrowthStrategy::Proportional { percent } => format!("proportional:{percent}"),
+        };
+        let deferred_collection = match self.deferred_collection {
+            DeferredCollection::RunImmediately => "run-immediately",
+            DeferredCollection::WaitForNextTrigger => "wait-for-next-trigger",
+        };
+
+        let mut vars = vec![
+            (ENV_AUTO_COLLECT, self.auto_collect.to_string()),
+            (ENV_BYTES_THRESHOLD, threshold.bytes_threshold.to_string()),
+            (ENV_ADJUSTMENT_PERCENT, threshold.adjustment_percent.to_string()),
+            (ENV_BUFFERED_THRESHOLD, optional(threshold.buffered_threshold)),
+            (ENV_MIN_BYTES_THRESHOLD, threshold.min_bytes_threshold.to_string()),
+            (ENV_MAX_BYTES_THRESHOLD, threshold.max_bytes_threshold.to_string()),
+            (ENV_GROWTH_STRATEGY, growth_strategy),
+            (ENV_MAX_HEAP_BYTES, optional(self.max_heap_bytes)),
+            (ENV_MAX_PAUSE_MICROS, optional(self.max_pause.map(|pause| pause.as_micros()))),
+            (ENV_DEFERRED_COLLECTION, deferred_collection.to_string()),
+        ];
+
+        match self.out_of_memory_policy {
+            OutOfMemoryPolicy::Panic => vars.push((ENV_OUT_OF_MEMORY_POLICY, "panic".to_string())),
+            OutOfMemoryPolicy::Error => vars.push((ENV_OUT_OF_MEMORY_POLICY, "error".to_string())),
+            OutOfMemoryPolicy::Callback(_) => {}, // Callbacks can't be expressed as environment variables
+        }
+
+        #[cfg(feature = "finalization")]
+        {
+            let finalization_policy = &self.finalization_policy;
+            vars.push((ENV_FINALIZATION_MAX_ROUNDS, finalization_policy.max_rounds.to_string()));
+            vars.push((ENV_FINALIZE_CREATED_OBJECTS, finalization_policy.finalize_created_objects.to_string()));
+            vars.push((ENV_LEFTOVER_OBJECTS, match finalization_policy.leftover_objects {
+                LeftoverObjects::Keep => "keep",
+                LeftoverObjects::DropWithoutFinalizing => "drop-without-finalizing",
+            }.to_string()));
+        }
+
+        vars.iter().map(|(variable, value)| format!("{variable}={value}")).collect::<Vec<_>>().join("\n")
+    }
+
+    fn to_shared(&self) -> SharedConfig {
+        SharedConfig {
+            threshold: self.threshold.clone(),
+            policy: self.policy.clone(),
+            max_heap_bytes: self.max_heap_bytes,
+            out_of_memory_policy: self.out_of_memory_policy,
+            max_pause: self.max_pause,
+            #[cfg(feature = "finalization")]
+            finalization_policy: self.finalization_policy,
+            deferred_collection: self.deferred_collection,
+            auto_collect: self.auto_collect,
+        }
+    }
+
+    fn apply_shared(&mut self, shared: &SharedConfig) {
+        self.threshold = shared.threshold.clone();
+        self.policy = shared.policy.clone();
+        self.max_heap_bytes = shared.max_heap_bytes;
+        self.out_of_memory_policy = shared.out_of_memory_policy;
+        self.max_pause = shared.max_pause;
+        #[cfg(feature = "finalization")]
+        {
+            self.finalization_policy = shared.finalization_policy;
+        }
+        self.deferred_collection = shared.deferred_collection;
+        self.auto_collect = shared.auto_collect;
+    }
+}
+
 impl Default for Config {
     #[inline]
     fn default() -> Self {
@@ -0,0 +1,205 @@
+//! Process-wide registry of the collector statistics of every thread.
+//!
+//! Every thread mirrors its counters into a [`ThreadCounters`] instance, which is registered into [`REGISTRY`]
+//! when the thread first updates its counters and deregistered when the thread exits.
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::thread::{self, ThreadId};
+
+use crate::utils;
+
+static REGISTRY: Mutex<Vec<Arc<ThreadCounters>>> = Mutex::new(Vec::new());
+
+utils::rust_cc_thread_local! {
+    static THREAD_COUNTERS: ThreadCountersHandle = const { ThreadCountersHandle { counters: OnceLock::new() } };
+}
+
+struct ThreadCounters {
+    thread_id: ThreadId,
+    thread_name: Option<String>,
+    allocated_bytes: AtomicUsize,
+    executions_count: AtomicUsize,
+    buffered_objects_count: AtomicUsize,
+}
+
+impl ThreadCounters {
+    fn snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            allocated_bytes: self.allocated_bytes.load(Ordering::Relaxed),
+            executions_count: self.executions_count.load(Ordering::Relaxed),
+            buffered_objects_count: self.buffered_objects_count.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Deregisters the counters of the thread from [`REGISTRY`] when the thread exits.
+struct ThreadCountersHandle {
+    counters: OnceLock<Arc<ThreadCounters>>,
+}
+
+impl ThreadCountersHandle {
+    #[inline]
+    fn counters(&self) -> &ThreadCounters {
+        self.counters.get_or_init(|| {
+            utils::cold();
+
+            let current = thread::current();
+            let counters = Arc::new(ThreadCounters {
+                thread_id: current.id(),
+                thread_name: current.name().map(String::from),
+                allocated_bytes: AtomicUsize::new(0),
+                executions_count: AtomicUsize::new(0),
+                buffered_objects_count: AtomicUsize::new(0),
+            });
+            REGISTRY.lock().unwrap_or_else(PoisonError::into_inner).push(counters.clone());
+            counters
+        })
+    }
+}
+
+impl Drop for ThreadCountersHandle {
+    fn drop(&mut self) {
+        if let Some(counters) = self.counters.get() {
+            let mut registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+            registry.retain(|elem| !Arc::ptr_eq(elem, counters));
+        }
+    }
+}
+
+#[inline]
+fn update(f: impl FnOnce(&ThreadCounters)) {
+    // Updates happening after the thread's counters have been deregistered are ignored
+    let _ = THREAD_COUNTERS.try_with(|handle| f(handle.counters()));
+}
+
+#[inline]
+pub(crate) fn record_allocated_bytes(bytes: usize) {
+    update(|counters| counters.allocated_bytes.store(bytes, Ordering::Relaxed));
+}
+
+#[inline]
+pub(crate) fn record_executions_count(count: usize) {
+    update(|counters| counters.executions_count.store(count, Ordering::Relaxed));
+}
+
+#[inline]
+pub(crate) fn record_buffered_objects_count(count: usize) {
+    update(|counters| counters.buffered_objects_count.store(count, Ordering::Relaxed));
+}
+
+/// Returns a snapshot of the collector statistics of every thread which is currently using the collector.
+///
+/// Threads are registered the first time they allocate a [`Cc`][`crate::Cc`] and deregistered when they exit.
+/// Since every thread updates its own statistics independently, the returned snapshots aren't taken at the same instant.
+///
+/// # Example
+/// ```rust
+///# use rust_cc::*;
+///# use rust_cc::state::global_stats;
+/// let _cc = Cc::new(5);
+///
+/// let stats = global_stats();
+/// assert!(stats.total().allocated_bytes() > 0);
+/// assert!(stats.threads().iter().any(|thread| thread.thread_id() == std::thread::current().id()));
+/// ```
+pub fn global_stats() -> GlobalStats {
+    let registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+
+    let threads: Vec<ThreadStats> = registry.iter().map(|counters| ThreadStats {
+        thread_id: counters.thread_id,
+        thread_name: counters.thread_name.clone(),
+        stats: counters.snapshot(),
+    }).collect();
+
+    let total = threads.iter().fold(StatsSnapshot::default(), |total, thread| StatsSnapshot {
+        allocated_bytes: total.allocated_bytes + thread.stats.allocated_bytes,
+        executions_count: total.executions_count + thread.stats.executions_count,
+        buffered_objects_count: total.buffered_objects_count + thread.stats.buffered_objects_count,
+    });
+
+    GlobalStats { threads, total }
+}
+
+/// The collector statistics of every thread, returned by [`global_stats`].
+#[derive(Debug, Clone)]
+pub struct GlobalStats {
+    threads: Vec<ThreadStats>,
+    total: StatsSnapshot,
+}
+
+impl GlobalStats {
+    /// Returns the statistics of every registered thread.
+    #[inline]
+    pub fn threads(&self) -> &[ThreadStats] {
+        &self.threads
+    }
+
+    /// Returns the sum of the statistics of every registered thread.
+    #[inline]
+    pub fn total(&self) -> StatsSnapshot {
+        self.total
+    }
+}
+
+/// The collector statistics of a single thread.
+#[derive(Debug, Clone)]
+pub struct ThreadStats {
+    thread_id: ThreadId,
+    thread_name: Option<String>,
+    stats: StatsSnapshot,
+}
+
+impl ThreadStats {
+    /// Returns the id of the thread.
+    #[inline]
+    pub fn thread_id(&self) -> ThreadId {
+        self.thread_id
+    }
+
+    /// Returns the name of the thread, if it has one.
+    #[inline]
+    pub fn thread_name(&self) -> Option<&str> {
+        self.thread_name.as_deref()
+    }
+
+    /// Returns the statistics of the thread.
+    #[inline]
+    pub fn stats(&self) -> StatsSnapshot {
+        self.stats
+    }
+}
+
+/// A snapshot of the collector statistics.
+///
+/// See [`allocated_bytes`][`fn@crate::state::allocated_bytes`], [`executions_count`][`fn@crate::state::executions_count`]
+/// and [`buffered_objects_count`][`fn@crate::state::buffered_objects_count`] for more details.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatsSnapshot {
+    allocated_bytes: usize,
+    executions_count: usize,
+    buffered_objects_count: usize,
+}
+
+impl StatsSnapshot {
+    /// Returns the number of allocated bytes managed by the garbage collector.
+    #[inline]
+    pub fn allocated_bytes(&self) -> usize {
+        self.allocated_bytes
+    }
+
+    /// Returns the number of executed collections.
+    #[inline]
+    pub fn executions_count(&self) -> usize {
+        self.executions_count
+    }
+
+    /// Returns the number of objects buffered to be processed in the next collection.
+    #[inline]
+    pub fn buffered_objects_count(&self) -> usize {
+        self.buffered_objects_count
+    }
+}
@@ -0,0 +1,1138 @@
+use alloc::collections::{BTreeMap, VecDeque};
+#[cfg(feature = "allocation-sites")]
+use alloc::string::ToString;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt::{self, Write};
+#[cfg(feature = "allocation-sites")]
+use core::panic::Location;
+use core::ptr::NonNull;
+use thiserror::Error;
+
+use crate::{Cc, Trace};
+#[cfg(feature = "allocation-sites")]
+use crate::allocation_sites::SiteStats;
+use crate::cc::CcBox;
+use crate::list::{List, ListMethods};
+use crate::state::{CollectionReport, CollectionTrigger, replace_state_field, State, try_state};
+use crate::trace::{Context, ContextInner};
+use crate::utils;
+#[cfg(feature = "weak-ptr")]
+use crate::weak::weak_metadata::WeakMetadata;
+
+utils::rust_cc_thread_local! {
+    static REGISTRY: RefCell<Registry> = const { RefCell::new(Registry::new()) };
+}
+
+struct Registry {
+    objects: BTreeMap<NonNull<CcBox<()>>, Entry>,
+    next_id: u64, // Used to distinguish objects allocated at the same address at different times
+}
+
+impl Registry {
+    #[inline]
+    const fn new() -> Self {
+        Self {
+            objects: BTreeMap::new(),
+            next_id: 0,
+        }
+    }
+}
+
+struct Entry {
+    id: u64,
+    #[cfg(feature = "weak-ptr")]
+    weak_metadata: Option<NonNull<WeakMetadata>>,
+}
+
+impl Entry {
+    #[inline]
+    fn weak_count(&self) -> u32 {
+        #[cfg(feature = "weak-ptr")]
+        {
+            self.weak_metadata.map_or(0, |metadata| unsafe { metadata.as_ref() }.counter() as u32)
+        }
+
+        #[cfg(not(feature = "weak-ptr"))]
+        {
+            0
+        }
+    }
+}
+
+#[inline]
+fn with_registry(f: impl FnOnce(&mut Registry)) {
+    // The registry may have already been destroyed if the thread is exiting
+    let _ = REGISTRY.try_with(|registry| {
+        if let Ok(mut registry) = registry.try_borrow_mut() {
+            f(&mut registry);
+        }
+    });
+}
+
+#[inline]
+pub(crate) fn register(ptr: NonNull<CcBox<()>>) {
+    with_registry(|registry| {
+        let id = registry.next_id;
+        registry.next_id += 1;
+        registry.objects.insert(ptr, Entry {
+            id,
+            #[cfg(feature = "weak-ptr")]
+            weak_metadata: None,
+        });
+    });
+}
+
+#[inline]
+pub(crate) fn deregister(ptr: NonNull<CcBox<()>>) {
+    with_registry(|registry| {
+        registry.objects.remove(&ptr);
+    });
+}
+
+#[cfg(feature = "weak-ptr")]
+#[inline]
+pub(crate) fn set_weak_metadata(ptr: NonNull<CcBox<()>>, metadata: NonNull<WeakMetadata>) {
+    with_registry(|registry| {
+        if let Some(entry) = registry.objects.get_mut(&ptr) {
+            entry.weak_metadata = Some(metadata);
+        }
+    });
+}
+
+/// Takes a [`HeapDump`] of the objects allocated by the current thread.
+///
+/// Objects are traced to discover the edges of the object graph, so the same rules of [`Trace`][`crate::Trace`]
+/// implementations apply.
+///
+/// # Errors
+///
+/// Returns [`IntrospectionError::Collecting`] if called during a collection or while an object is being dropped.
+pub fn heap_dump() -> Result<HeapDump, IntrospectionError> {
+    try_state(|state| {
+        let entries = live_objects(state)?;
+        let ptrs: Vec<_> = entries.iter().map(|object| object.ptr).collect();
+        let graph = object_graph(state, &ptrs);
+
+        let objects = entries.into_iter().zip(graph).map(|(LiveObject { ptr, weak_count: _weak_count, .. }, edges)| {
+            let cc_box = unsafe { ptr.as_ref() };
+
+            ObjectInfo {
+                address: ptr.as_ptr() as usize,
+                type_name: CcBox::type_name(ptr),
+                size: cc_box.layout().size(),
+                strong_count: cc_box.counter_marker().counter(),
+                #[cfg(feature = "weak-ptr")]
+                weak_count: _weak_count,
+                #[cfg(feature = "allocation-sites")]
+                allocation_site: crate::allocation_sites::site_of(ptr),
+                edges,
+            }
+        }).collect();
+
+        Ok(HeapDump { objects })
+    }).unwrap_or(Err(IntrospectionError::AccessError))
+}
+
+/// A registered object.
+struct LiveObject {
+    ptr: NonNull<CcBox<()>>,
+    id: u64,
+    weak_count: u32,
+}
+
+/// Returns the registered objects.
+fn live_objects(state: &State) -> Result<Vec<LiveObject>, IntrospectionError> {
+    // Objects may have been already dropped during collections or while dropping a Cc
+    if state.is_collecting() || state.is_dropping() {
+        return Err(IntrospectionError::Collecting);
+    }
+
+    // Copy the registry to avoid keeping it borrowed while calling Trace implementations
+    let entries: Vec<LiveObject> = REGISTRY.try_with(|registry| {
+        registry.try_borrow().map(|registry| {
+            registry.objects.iter().map(|(ptr, entry)| LiveObject {
+                ptr: *ptr,
+                id: entry.id,
+                weak_count: entry.weak_count(),
+            }).collect()
+        }).map_err(|_| IntrospectionError::AccessError)
+    }).unwrap_or(Err(IntrospectionError::AccessError))?;
+
+    // Objects with a strong count of 0 aren't initialized yet (see Cc::new_cyclic)
+    Ok(entries.into_iter().filter(|object| {
+        unsafe { object.ptr.as_ref() }.counter_marker().counter() != 0
+    }).collect())
+}
+
+/// Traces every object, returning for each one the indexes into `objects` of the objects it points to.
+/// Edges to objects not contained into `objects` are discarded.
+fn object_graph(state: &State, objects: &[NonNull<CcBox<()>>]) -> Vec<Vec<usize>> {
+    let indexes: BTreeMap<NonNull<CcBox<()>>, usize> = objects.iter().enumerate().map(|(i, ptr)| (*ptr, i)).collect();
+
+    // Trace implementations expect the collector to be collecting
+    let _guard = replace_state_field!(collecting, true, state);
+
+    let mut edges = Vec::new();
+    objects.iter().map(|&ptr| {
+        edges.clear();
+        CcBox::trace_inner(ptr, &mut Context::new(ContextInner::Introspection { edges: &mut edges }));
+        edges.iter().filter_map(|edge| indexes.get(edge).copied()).collect()
+    }).collect()
+}
+
+/// An error returned by [`heap_dump`] and the other heap introspection functions.
+#[non_exhaustive]
+#[derive(Error, Debug)]
+pub enum IntrospectionError {
+    /// The garbage collector state couldn't be accessed.
+    #[error("couldn't access the state")]
+    AccessError,
+
+    /// The heap cannot be inspected during a collection or while an object is being dropped.
+    #[error("cannot inspect the heap during a collection or while an object is being dropped")]
+    Collecting,
+
+    /// The object passed to [`explain_retention`] isn't tracked by the current thread, for example because it
+    /// was allocated by another thread.
+    #[error("the object isn't tracked by the current thread")]
+    NotTracked,
+}
+
+/// The live objects allocated by a thread, returned by [`heap_dump`].
+#[derive(Debug, Clone)]
+pub struct HeapDump {
+    objects: Vec<ObjectInfo>,
+}
+
+impl HeapDump {
+    /// Returns the live objects.
+    ///
+    /// The [`edges`][`ObjectInfo::edges`] of every object are indexes into the returned slice.
+    #[inline]
+    pub fn objects(&self) -> &[ObjectInfo] {
+        &self.objects
+    }
+
+    /// Returns the live objects grouped by allocation site. Objects without an allocation site are ignored.
+    ///
+    /// See the [`allocation_sites` module documentation][`mod@crate::allocation_sites`] for more details.
+    #[cfg(feature = "allocation-sites")]
+    pub fn allocation_sites(&self) -> Vec<SiteStats> {
+        SiteStats::group(self.objects.iter().filter_map(|object| object.allocation_site.map(|site| (site, object.size))))
+    }
+
+    /// Returns the object graph in the [DOT](https://graphviz.org/doc/info/lang.html) format.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        let _ = self.write_dot(&mut dot); // Writing to a String never fails
+        dot
+    }
+
+    /// Returns the object graph in the JSON format.
+    ///
+    /// The returned JSON is an object with a single `objects` field, containing an array of objects with the `id`,
+    /// `address`, `type_name`, `size`, `strong_count`, `weak_count` (only when the `weak-ptr` feature is enabled),
+    /// `allocation_site` (only when the `allocation-sites` feature is enabled, `null` if not available) and `edges` fields.
+    /// `edges` is an array of ids.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        let _ = self.write_json(&mut json); // Writing to a String never fails
+        json
+    }
+
+    fn write_dot(&self, w: &mut impl Write) -> fmt::Result {
+        writeln!(w, "digraph heap {{")?;
+        for (id, object) in self.objects.iter().enumerate() {
+            write!(w, "    n{id} [label=\"")?;
+            write_escaped(w, object.type_name)?;
+            write!(w, "\\n{} bytes, strong: {}", object.size, object.strong_count)?;
+            #[cfg(feature = "weak-ptr")]
+            write!(w, ", weak: {}", object.weak_count)?;
+            #[cfg(feature = "allocation-sites")]
+            if let Some(site) = object.allocation_site {
+                write!(w, "\\nat ")?;
+                write_escaped(w, &site.to_string())?;
+            }
+            writeln!(w, "\"];")?;
+        }
+        for (id, object) in self.objects.iter().enumerate() {
+            for edge in &object.edges {
+                writeln!(w, "    n{id} -> n{edge};")?;
+            }
+        }
+        writeln!(w, "}}")
+    }
+
+    fn write_json(&self, w: &mut impl Write) -> fmt::Result {
+        write!(w, "{{\"objects\":[")?;
+        for (id, object) in self.objects.iter().enumerate() {
+            if id != 0 {
+                write!(w, ",")?;
+            }
+            write!(w, "{{\"id\":{id},\"address\":\"{:#x}\",\"type_name\":\"", object.address)?;
+            write_escaped(w, object.type_name)?;
+            write!(w, "\",\"size\":{},\"strong_count\":{}", object.size, object.strong_count)?;
+            #[cfg(feature = "weak-ptr")]
+            write!(w, ",\"weak_count\":{}", object.weak_count)?;
+            #[cfg(feature = "allocation-sites")]
+            match object.allocation_site {
+                Some(site) => {
+                    write!(w, ",\"allocation_site\":\"")?;
+                    write_escaped(w, &site.to_string())?;
+                    write!(w, "\"")?;
+                },
+                None => write!(w, ",\"allocation_site\":null")?,
+            }
+            write!(w, ",\"edges\":[")?;
+            for (i, edge) in object.edges.iter().enumerate() {
+                if i != 0 {
+                    write!(w, ",")?;
+                }
+                write!(w, "{edge}")?;
+            }
+            write!(w, "]}}")?;
+        }
+        write!(w, "]}}")
+    }
+}
+
+/// Escapes `"`, `\` and the control characters, which aren't allowed inside JSON strings.
+/// The result can be used for both DOT and JSON strings.
+pub(crate) fn write_escaped(w: &mut impl Write, s: &str) -> fmt::Result {
+    for c in s.chars() {
+        match c {
+            '"' | '\\' => write!(w, "\\{c}")?,
+            '\n' => w.write_str("\\n")?,
+            '\r' => w.write_str("\\r")?,
+            '\t' => w.write_str("\\t")?,
+            c if c < ' ' => write!(w, "\\u{:04x}", c as u32)?,
+            c => w.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+/// A live object inside a [`HeapDump`].
+#[derive(Debug, Clone)]
+pub struct ObjectInfo {
+    address: usize,
+    type_name: &'static str,
+    size: usize,
+    strong_count: u32,
+    #[cfg(feature = "weak-ptr")]
+    weak_count: u32,
+    #[cfg(feature = "allocation-sites")]
+    allocation_site: Option<&'static Location<'static>>,
+    edges: Vec<usize>,
+}
+
+impl ObjectInfo {
+    /// Returns the address of the allocation.
+    #[inline]
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    /// Returns the name of the type of the object, as returned by [`type_name`][`core::any::type_name`].
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns the size in bytes of the allocation.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the number of [`Cc`][`crate::Cc`]s to the object.
+    #[inline]
+    pub fn strong_count(&self) -> u32 {
+        self.strong_count
+    }
+
+    /// Returns the number of [`Weak`][`crate::weak::Weak`]s to the object.
+    #[cfg(feature = "weak-ptr")]
+    #[inline]
+    pub fn weak_count(&self) -> u32 {
+        self.weak_count
+    }
+
+    /// Returns the location of the code which allocated the object, if available.
+    ///
+    /// See the [`allocation_sites` module documentation][`mod@crate::allocation_sites`] for more details.
+    #[cfg(feature = "allocation-sites")]
+    #[inline]
+    pub fn allocation_site(&self) -> Option<&'static Location<'static>> {
+        self.allocation_site
+    }
+
+    /// Returns the indexes into [`HeapDump::objects`] of the objects pointed by this object.
+    #[inline]
+    pub fn edges(&self) -> &[usize] {
+        &self.edges
+    }
+}
+
+/// The objects allocated by the current thread at a point in time.
+///
+/// Two snapshots can be compared using [`diff`][`HeapSnapshot::diff`] to find the objects allocated and freed between them.
+///
+/// # Example
+/// ```rust
+///# use rust_cc::*;
+///# use rust_cc::introspection::*;
+/// let before = HeapSnapshot::capture().unwrap();
+///
+/// let cc = Cc::new(5u32);
+/// // ...
+/// drop(cc);
+/// collect_cycles();
+///
+/// let diff = HeapSnapshot::capture().unwrap().diff(&before);
+/// assert!(diff.is_empty(), "{diff}");
+/// ```
+#[derive(Debug, Clone)]
+pub struct HeapSnapshot {
+    objects: BTreeMap<u64, SnapshotObject>, // Indexed by the id of the allocation
+}
+
+#[derive(Debug, Clone)]
+struct SnapshotObject {
+    address: usize,
+    type_name: &'static str,
+    size: usize,
+    edges: usize,
+}
+
+impl HeapSnapshot {
+    /// Captures a snapshot of the objects allocated by the current thread.
+    ///
+    /// Objects are traced to count the objects they point to, so the same rules of [`Trace`] implementations apply.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IntrospectionError::Collecting`] if called during a collection or while an object is being dropped.
+    pub fn capture() -> Result<HeapSnapshot, IntrospectionError> {
+        try_state(|state| {
+            let entries = live_objects(state)?;
+            let ptrs: Vec<_> = entries.iter().map(|object| object.ptr).collect();
+            let graph = object_graph(state, &ptrs);
+
+            let objects = entries.into_iter().zip(graph).map(|(object, edges)| {
+                (object.id, SnapshotObject {
+                    address: object.ptr.as_ptr() as usize,
+                    type_name: CcBox::type_name(object.ptr),
+                    size: unsafe { object.ptr.as_ref() }.layout().size(),
+                    edges: edges.len(),
+                })
+            }).collect();
+
+            Ok(HeapSnapshot { objects })
+        }).unwrap_or(Err(IntrospectionError::AccessError))
+    }
+
+    /// Returns the number of objects in the snapshot.
+    #[inline]
+    pub fn objects_count(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Returns the total size in bytes of the objects in the snapshot.
+    #[inline]
+    pub fn bytes(&self) -> usize {
+        self.objects.values().map(|object| object.size).sum()
+    }
+
+    /// Returns the differences between this snapshot and an `earlier` one.
+    ///
+    /// Objects are identified by their allocation, so an object allocated at the same address of a freed one is
+    /// correctly reported as a new object.
+    pub fn diff(&self, earlier: &HeapSnapshot) -> HeapDiff {
+        fn group<'a>(objects: impl Iterator<Item = &'a SnapshotObject>) -> Vec<TypeDelta> {
+            let mut groups: BTreeMap<&'static str, TypeDelta> = BTreeMap::new();
+            for object in objects {
+                let delta = groups.entry(object.type_name).or_insert(TypeDelta {
+                    type_name: object.type_name,
+                    count: 0,
+                    bytes: 0,
+                });
+                delta.count += 1;
+                delta.bytes += object.size;
+            }
+
+            let mut deltas: Vec<TypeDelta> = groups.into_values().collect();
+            // The types with more bytes first
+            deltas.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(a.type_name.cmp(b.type_name)));
+            deltas
+        }
+
+        let allocated = group(self.objects.iter().filter(|(id, _)| !earlier.objects.contains_key(id)).map(|(_, object)| object));
+        let freed = group(earlier.objects.iter().filter(|(id, _)| !self.objects.contains_key(id)).map(|(_, object)| object));
+
+        let grown = self.objects.iter().filter_map(|(id, object)| {
+            let previous = earlier.objects.get(id)?;
+            (object.edges > previous.edges).then_some(GrownObject {
+                address: object.address,
+                type_name: object.type_name,
+                previous_edges: previous.edges,
+                edges: object.edges,
+            })
+        }).collect();
+
+        HeapDiff { allocated, freed, grown }
+    }
+}
+
+/// The differences between two [`HeapSnapshot`]s, returned by [`HeapSnapshot::diff`].
+///
+/// The [`Display`][`fmt::Display`] implementation prints a human-readable report of the differences.
+#[derive(Debug, Clone)]
+pub struct HeapDiff {
+    allocated: Vec<TypeDelta>,
+    freed: Vec<TypeDelta>,
+    grown: Vec<GrownObject>,
+}
+
+impl HeapDiff {
+    /// Returns the objects allocated between the two snapshots which are still alive, grouped by type.
+    ///
+    /// The types with more bytes come first.
+    #[inline]
+    pub fn allocated(&self) -> &[TypeDelta] {
+        &self.allocated
+    }
+
+    /// Returns the objects freed between the two snapshots, grouped by type.
+    ///
+    /// The types with more bytes come first.
+    #[inline]
+    pub fn freed(&self) -> &[TypeDelta] {
+        &self.freed
+    }
+
+    /// Returns the objects present in both snapshots which now point to more objects than before.
+    #[inline]
+    pub fn grown(&self) -> &[GrownObject] {
+        &self.grown
+    }
+
+    /// Returns `true` if no object has been allocated, freed or has grown between the two snapshots.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.allocated.is_empty() && self.freed.is_empty() && self.grown.is_empty()
+    }
+}
+
+impl fmt::Display for HeapDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "no differences between the heap snapshots");
+        }
+
+        for (title, deltas) in [("allocated", &self.allocated), ("freed", &self.freed)] {
+            if !deltas.is_empty() {
+                writeln!(f, "{title}:")?;
+                for delta in deltas {
+                    writeln!(f, "    {} x {} ({} bytes)", delta.count, delta.type_name, delta.bytes)?;
+                }
+            }
+        }
+
+        if !self.grown.is_empty() {
+            writeln!(f, "grown:")?;
+            for object in &self.grown {
+                writeln!(
+                    f, "    {} at {:#x} ({} -> {} edges)",
+                    object.type_name, object.address, object.previous_edges, object.edges
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The objects of a single type inside a [`HeapDiff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeDelta {
+    type_name: &'static str,
+    count: usize,
+    bytes: usize,
+}
+
+impl TypeDelta {
+    /// Returns the name of the type, as returned by [`type_name`][`core::any::type_name`].
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns the number of objects.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the total size in bytes of the objects.
+    #[inline]
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+/// An object inside a [`HeapDiff`] which points to more objects than before.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrownObject {
+    address: usize,
+    type_name: &'static str,
+    previous_edges: usize,
+    edges: usize,
+}
+
+impl GrownObject {
+    /// Returns the address of the allocation.
+    #[inline]
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    /// Returns the name of the type of the object, as returned by [`type_name`][`core::any::type_name`].
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns the number of objects pointed by the object in the earlier snapshot.
+    #[inline]
+    pub fn previous_edges(&self) -> usize {
+        self.previous_edges
+    }
+
+    /// Returns the number of objects pointed by the object in the later snapshot.
+    #[inline]
+    pub fn edges(&self) -> usize {
+        self.edges
+    }
+}
+
+/// Explains why the object pointed by `cc` is still alive.
+///
+/// The objects allocated by the current thread are traced to find the references between them. The references to an
+/// object which aren't found while tracing (i.e. its strong count minus the references coming from the other objects) are
+/// "external" references, coming from local variables or other data structures. An object is kept alive by its external
+/// references and by the external references of any object it is reachable from.
+///
+/// The returned [`RetentionExplanation`] contains the shortest chain of references from an externally referenced object
+/// to the object pointed by `cc`. The reference `cc` itself isn't counted as an external reference.
+///
+/// This function is also re-exported as [`state::explain_retention`][`fn@crate::state::explain_retention`].
+///
+/// # Errors
+///
+/// Returns [`IntrospectionError::Collecting`] if called during a collection or while an object is being dropped,
+/// and [`IntrospectionError::NotTracked`] if the object pointed by `cc` isn't tracked by the current thread.
+///
+/// # Example
+/// ```rust
+///# use rust_cc::*;
+///# use rust_cc::introspection::*;
+///# use std::cell::RefCell;
+/// struct Node {
+///     next: RefCell<Option<Cc<Node>>>,
+/// }
+///# unsafe impl Trace for Node {
+///#     fn trace(&self, ctx: &mut Context<'_>) {
+///#         self.next.trace(ctx);
+///#     }
+///# }
+///# impl Finalize for Node {}
+///
+/// let leaf = Cc::new(Node { next: RefCell::new(None) });
+/// let holder = Cc::new(Node { next: RefCell::new(Some(leaf.clone())) });
+///
+/// let explanation = explain_retention(&leaf).unwrap();
+/// let chain = explanation.chain();
+/// assert_eq!(2, chain.len());
+/// assert_eq!(1, chain[0].external_count()); // holder is referenced by a local variable
+///
+/// drop(holder);
+/// assert!(explain_retention(&leaf).unwrap().is_garbage());
+/// ```
+pub fn explain_retention<T: ?Sized + Trace + 'static>(cc: &Cc<T>) -> Result<RetentionExplanation, IntrospectionError> {
+    try_state(|state| {
+        let objects: Vec<_> = live_objects(state)?.into_iter().map(|object| object.ptr).collect();
+        let target_ptr: NonNull<CcBox<()>> = cc.inner_ptr().cast();
+        let target = objects.iter().position(|ptr| *ptr == target_ptr).ok_or(IntrospectionError::NotTracked)?;
+        let graph = object_graph(state, &objects);
+
+        // Count the references found while tracing, like the tracing counter in the counting phase of the collector
+        let mut traced_counts = alloc::vec![0u32; objects.len()];
+        let mut predecessors: Vec<Vec<usize>> = alloc::vec![Vec::new(); objects.len()];
+        for (i, edges) in graph.iter().enumerate() {
+            for &edge in edges {
+                traced_counts[edge] += 1;
+                predecessors[edge].push(i);
+            }
+        }
+
+        let external_count = |i: usize| {
+            let counter = unsafe { objects[i].as_ref() }.counter_marker().counter();
+            let external = counter.saturating_sub(traced_counts[i]);
+            if i == target {
+                external.saturating_sub(1) // Don't count cc
+            } else {
+                external
+            }
+        };
+
+        // Breadth-first search backwards from the target, stopping at the first externally referenced object
+        let mut next_in_chain: Vec<Option<usize>> = alloc::vec![None; objects.len()];
+        let mut visited = alloc::vec![false; objects.len()];
+        let mut queue = VecDeque::new();
+        visited[target] = true;
+        queue.push_back(target);
+
+        let mut root = None;
+        while let Some(i) = queue.pop_front() {
+            if external_count(i) != 0 {
+                root = Some(i);
+                break;
+            }
+
+            for &predecessor in &predecessors[i] {
+                if !visited[predecessor] {
+                    visited[predecessor] = true;
+                    next_in_chain[predecessor] = Some(i);
+                    queue.push_back(predecessor);
+                }
+            }
+        }
+
+        let mut chain = Vec::new();
+        let mut current = root;
+        while let Some(i) = current {
+            let ptr = objects[i];
+            let cc_box = unsafe { ptr.as_ref() };
+
+            chain.push(RetainerInfo {
+                address: ptr.as_ptr() as usize,
+                type_name: CcBox::type_name(ptr),
+                size: cc_box.layout().size(),
+                strong_count: cc_box.counter_marker().counter(),
+                external_count: external_count(i),
+            });
+            current = next_in_chain[i];
+        }
+
+        Ok(RetentionExplanation { chain })
+    }).unwrap_or(Err(IntrospectionError::AccessError))
+}
+
+/// Why an object is still alive, returned by [`explain_retention`].
+#[derive(Debug, Clone)]
+pub struct RetentionExplanation {
+    chain: Vec<RetainerInfo>,
+}
+
+impl RetentionExplanation {
+    /// Returns the chain of objects keeping the object alive.
+    ///
+    /// The first object is externally referenced, every object points to the next one, and the last object is the
+    /// one passed to [`explain_retention`]. The returned slice is empty if the object is not externally
+    /// referenced by any object (see [`is_garbage`][`RetentionExplanation::is_garbage`]).
+    #[inline]
+    pub fn chain(&self) -> &[RetainerInfo] {
+        &self.chain
+    }
+
+    /// Returns whether the object is kept alive only by the reference passed to [`explain_retention`] and by
+    /// garbage cycles, i.e. whether it would be collected if that reference was dropped.
+    #[inline]
+    pub fn is_garbage(&self) -> bool {
+        self.chain.is_empty()
+    }
+}
+
+/// An object inside the chain of a [`RetentionExplanation`].
+#[derive(Debug, Clone)]
+pub struct RetainerInfo {
+    address: usize,
+    type_name: &'static str,
+    size: usize,
+    strong_count: u32,
+    external_count: u32,
+}
+
+impl RetainerInfo {
+    /// Returns the address of the allocation.
+    #[inline]
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    /// Returns the name of the type of the object, as returned by [`type_name`][`core::any::type_name`].
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns the size in bytes of the allocation.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the number of [`Cc`]s to the object.
+    #[inline]
+    pub fn strong_count(&self) -> u32 {
+        self.strong_count
+    }
+
+    /// Returns the number of [`Cc`]s to the object which haven't been found while tracing, i.e. the
+    /// strong count minus the number of references coming from the other objects.
+    #[inline]
+    pub fn external_count(&self) -> u32 {
+        self.external_count
+    }
+}
+
+/// Immediately executes the cycle collection algorithm like [`collect_cycles`][`fn@crate::collect_cycles`], recording
+/// the garbage cycles which have been collected and the objects which have been found alive.
+///
+/// Recording the diagnostics requires tracing the collected objects once more, so this function is slower than
+/// [`collect_cycles`][`fn@crate::collect_cycles`] and should be used only for debugging purposes.
+///
+/// Returns [`None`] if no collection has been executed.
+///
+/// # Example
+/// ```rust
+///# use rust_cc::*;
+///# use rust_cc::introspection::*;
+///# use std::cell::RefCell;
+/// struct Node {
+///     next: RefCell<Option<Cc<Node>>>,
+/// }
+///# unsafe impl Trace for Node {
+///#     fn trace(&self, ctx: &mut Context<'_>) {
+///#         self.next.trace(ctx);
+///#     }
+///# }
+///# impl Finalize for Node {}
+///
+/// let a = Cc::new(Node { next: RefCell::new(None) });
+/// let b = Cc::new(Node { next: RefCell::new(Some(a.clone())) });
+/// *a.next.borrow_mut() = Some(b.clone());
+/// drop(a);
+/// drop(b);
+///
+/// let diagnostics = collect_cycles_with_diagnostics().unwrap();
+/// for cycle in diagnostics.garbage_cycles() {
+///     for member in cycle.members() {
+///         println!("{} ({} bytes)", member.type_name(), member.size());
+///     }
+/// }
+/// ```
+pub fn collect_cycles_with_diagnostics() -> Option<CollectionDiagnostics> {
+    try_state(|state| {
+        // Don't start recording if the collection cannot be executed (see run_collection)
+        if state.is_collecting() || state.is_running_hook() {
+            return None;
+        }
+
+        struct DropGuard<'a> {
+            state: &'a State,
+        }
+
+        impl<'a> Drop for DropGuard<'a> {
+            #[inline]
+            fn drop(&mut self) {
+                // Stop recording, also if the collection panicked
+                self.state.diagnostics().replace(None);
+            }
+        }
+
+        state.diagnostics().replace(Some(CollectionDiagnostics::new()));
+        let _drop_guard = DropGuard { state };
+
+        let report = crate::run_collection(CollectionTrigger::Manual)?;
+
+        let mut diagnostics = state.diagnostics().take()?;
+        diagnostics.report = report;
+        Some(diagnostics)
+    }).ok().flatten()
+}
+
+#[inline]
+fn is_recording(state: &State) -> bool {
+    state.diagnostics().try_borrow().is_ok_and(|diagnostics| diagnostics.is_some())
+}
+
+#[inline]
+fn record(state: &State, f: impl FnOnce(&mut CollectionDiagnostics)) {
+    if let Ok(mut diagnostics) = state.diagnostics().try_borrow_mut() {
+        if let Some(diagnostics) = diagnostics.as_mut() {
+            f(diagnostics);
+        }
+    }
+}
+
+/// Records the objects of `root_list`, i.e. the objects found alive since they're referenced from outside the traced objects.
+pub(crate) fn record_roots(state: &State, root_list: &List) {
+    if !is_recording(state) {
+        return;
+    }
+
+    let roots: Vec<RootInfo> = root_list.iter().map(|ptr| {
+        let cc_box = unsafe { ptr.as_ref() };
+        let counter_marker = cc_box.counter_marker();
+
+        RootInfo {
+            address: ptr.as_ptr() as usize,
+            type_name: CcBox::type_name(ptr),
+            size: cc_box.layout().size(),
+            strong_count: counter_marker.counter(),
+            external_count: counter_marker.counter() - counter_marker.tracing_counter(),
+        }
+    }).collect();
+
+    record(state, |diagnostics| diagnostics.roots.extend(roots));
+}
+
+/// Records the garbage objects of `garbage_list`, grouped into their strongly connected components.
+pub(crate) fn record_garbage(state: &State, garbage_list: &List) {
+    if !is_recording(state) {
+        return;
+    }
+
+    let objects: Vec<NonNull<CcBox<()>>> = garbage_list.iter().collect();
+
+    // Only the edges between garbage objects are kept
+    let graph = object_graph(state, &objects);
+
+    let cycles: Vec<GarbageCycle> = strongly_connected_components(&graph).into_iter().map(|component| {
+        let positions: BTreeMap<usize, usize> = component.iter().enumerate().map(|(position, &i)| (i, position)).collect();
+        let members = component.iter().map(|&i| {
+            let ptr = objects[i];
+            CycleMember {
+                address: ptr.as_ptr() as usize,
+                type_name: CcBox::type_name(ptr),
+                size: unsafe { ptr.as_ref() }.layout().size(),
+                edges: graph[i].iter().filter_map(|edge| positions.get(edge).copied()).collect(),
+            }
+        }).collect();
+
+        GarbageCycle { members }
+    }).collect();
+
+    record(state, |diagnostics| diagnostics.garbage_cycles.extend(cycles));
+}
+
+/// Returns the strongly connected components of `graph` using Tarjan's algorithm.
+///
+/// `graph` contains the edges of every node. An iterative implementation is used to avoid overflowing the stack on long chains.
+fn strongly_connected_components(graph: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    const UNVISITED: usize = usize::MAX;
+
+    let mut index = alloc::vec![UNVISITED; graph.len()];
+    let mut low_link = alloc::vec![0; graph.len()];
+    let mut on_stack = alloc::vec![false; graph.len()];
+    let mut stack = Vec::new();
+    let mut call_stack: Vec<(usize, usize)> = Vec::new(); // (node, index of the next edge to visit)
+    let mut next_index = 0;
+    let mut components = Vec::new();
+
+    for root in 0..graph.len() {
+        if index[root] != UNVISITED {
+            continue;
+        }
+
+        index[root] = next_index;
+        low_link[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        call_stack.push((root, 0));
+
+        while let Some(&(node, next_edge)) = call_stack.last() {
+            if let Some(&successor) = graph[node].get(next_edge) {
+                call_stack.last_mut().unwrap().1 += 1;
+
+                if index[successor] == UNVISITED {
+                    index[successor] = next_index;
+                    low_link[successor] = next_index;
+                    next_index += 1;
+                    stack.push(successor);
+                    on_stack[successor] = true;
+                    call_stack.push((successor, 0));
+                } else if on_stack[successor] {
+                    low_link[node] = usize::min(low_link[node], index[successor]);
+                }
+            } else {
+                call_stack.pop();
+
+                if let Some(&(parent, _)) = call_stack.last() {
+                    low_link[parent] = usize::min(low_link[parent], low_link[node]);
+                }
+
+                if low_link[node] == index[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+    }
+
+    components
+}
+
+/// Diagnostics about a collection, returned by [`collect_cycles_with_diagnostics`].
+///
+/// When finalization is enabled, a collection may execute the collection algorithm more than once
+/// (see [`CollectionReport::iterations`]). In this case, the garbage cycles and the roots found by every iteration are recorded.
+#[derive(Debug, Clone)]
+pub struct CollectionDiagnostics {
+    report: CollectionReport,
+    garbage_cycles: Vec<GarbageCycle>,
+    roots: Vec<RootInfo>,
+}
+
+impl CollectionDiagnostics {
+    #[inline]
+    const fn new() -> Self {
+        Self {
+            report: CollectionReport::new(),
+            garbage_cycles: Vec::new(),
+            roots: Vec::new(),
+        }
+    }
+
+    /// Returns the [`CollectionReport`] of the collection.
+    #[inline]
+    pub fn report(&self) -> &CollectionReport {
+        &self.report
+    }
+
+    /// Returns the strongly connected components of the garbage which has been collected.
+    ///
+    /// Objects which aren't part of a cycle, but which have been collected since they were only referenced by garbage
+    /// cycles, are returned as components with a single member.
+    #[inline]
+    pub fn garbage_cycles(&self) -> &[GarbageCycle] {
+        &self.garbage_cycles
+    }
+
+    /// Returns the objects taken from the buffer of possible cycles which have been found alive, since they're
+    /// referenced from outside the traced objects.
+    ///
+    /// See [`buffered_objects_count`][`fn@crate::state::buffered_objects_count`] for more details about the buffer.
+    #[inline]
+    pub fn roots(&self) -> &[RootInfo] {
+        &self.roots
+    }
+}
+
+/// A strongly connected component of collected objects inside [`CollectionDiagnostics`].
+#[derive(Debug, Clone)]
+pub struct GarbageCycle {
+    members: Vec<CycleMember>,
+}
+
+impl GarbageCycle {
+    /// Returns the objects of the component.
+    ///
+    /// The [`edges`][`CycleMember::edges`] of every member are indexes into the returned slice.
+    #[inline]
+    pub fn members(&self) -> &[CycleMember] {
+        &self.members
+    }
+}
+
+/// A collected object inside a [`GarbageCycle`].
+///
+/// The object has already been deallocated, so the returned address is only useful to identify it.
+#[derive(Debug, Clone)]
+pub struct CycleMember {
+    address: usize,
+    type_name: &'static str,
+    size: usize,
+    edges: Vec<usize>,
+}
+
+impl CycleMember {
+    /// Returns the address of the allocation.
+    #[inline]
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    /// Returns the name of the type of the object, as returned by [`type_name`][`core::any::type_name`].
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns the size in bytes of the allocation.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the indexes into [`GarbageCycle::members`] of the members pointed by this object.
+    #[inline]
+    pub fn edges(&self) -> &[usize] {
+        &self.edges
+    }
+}
+
+/// An object found alive by a collection inside [`CollectionDiagnostics`].
+#[derive(Debug, Clone)]
+pub struct RootInfo {
+    address: usize,
+    type_name: &'static str,
+    size: usize,
+    strong_count: u32,
+    external_count: u32,
+}
+
+impl RootInfo {
+    /// Returns the address of the allocation.
+    #[inline]
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    /// Returns the name of the type of the object, as returned by [`type_name`][`core::any::type_name`].
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns the size in bytes of the allocation.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the number of [`Cc`][`crate::Cc`]s to the object at the time of the collection.
+    #[inline]
+    pub fn strong_count(&self) -> u32 {
+        self.strong_count
+    }
+
+    /// Returns the number of [`Cc`][`crate::Cc`]s to the object which haven't been found while tracing, i.e. the
+    /// strong count minus the number of references coming from the traced objects. These references kept the object alive.
+    #[inline]
+    pub fn external_count(&self) -> u32 {
+        self.external_count
+    }
+}
@@ -0,0 +1,353 @@
+//! Walk the object graph and enumerate the live objects.
+//!
+//! When the `graph-walk` feature is enabled, custom analyses can be built using [`Cc::visit_edges`] and
+//! [`walk_reachable`], which visit the object graph using the existing [`Trace`] implementations.
+//! [`Cc::reachable_size`] and [`Cc::retained_size`] can instead be used to know how much memory an object keeps alive.
+//! Walking the object graph doesn't add any overhead to allocations and deallocations.
+//!
+//! [`Cc`]: crate::Cc
+//! [`Cc::visit_edges`]: crate::Cc::visit_edges
+//! [`Cc::reachable_size`]: crate::Cc::reachable_size
+//! [`Cc::retained_size`]: crate::Cc::retained_size
+//! [`Trace`]: crate::Trace
+#![cfg_attr(feature = "heap-introspection", doc = r##"
+When the `heap-introspection` feature is enabled, every [`Cc`] allocated by the current thread is kept into a registry.
+[`heap_dump`] can be used to take a [`HeapDump`] containing every live object with its type name, size, reference counts
+and the objects it points to (which are discovered using [`Trace`]). A [`HeapDump`] can be exported as
+[DOT](https://graphviz.org/doc/info/lang.html) or JSON.
+
+Note that the registry adds an overhead to every allocation and deallocation.
+
+# Example
+```rust
+# use rust_cc::*;
+# use rust_cc::introspection::*;
+# use std::cell::RefCell;
+struct Node {
+    next: RefCell<Option<Cc<Node>>>,
+}
+# unsafe impl Trace for Node {
+#     fn trace(&self, ctx: &mut Context<'_>) {
+#         self.next.trace(ctx);
+#     }
+# }
+# impl Finalize for Node {}
+
+let node = Cc::new(Node { next: RefCell::new(None) });
+*node.next.borrow_mut() = Some(node.clone());
+
+let dump = heap_dump().unwrap();
+let object = &dump.objects()[0];
+assert_eq!(2, object.strong_count());
+assert_eq!(&[0], object.edges()); // The object points to itself
+
+println!("{}", dump.to_dot());
+# *node.next.borrow_mut() = None;
+```
+
+[`collect_cycles_with_diagnostics`] can instead be used to know which garbage cycles a collection has found
+and which objects it has found alive, while [`explain_retention`] explains why a single object is still alive.
+
+[`HeapSnapshot`]s can be used to find the objects allocated and freed between two points in time.
+"##)]
+
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::any::TypeId;
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+
+use crate::{Cc, Trace};
+use crate::cc::CcBox;
+use crate::state::{replace_state_field, state, State, try_state};
+use crate::trace::{Context, ContextInner};
+
+#[cfg(feature = "heap-introspection")]
+mod heap;
+
+#[cfg(feature = "heap-introspection")]
+pub use heap::{
+    collect_cycles_with_diagnostics, CollectionDiagnostics, CycleMember, explain_retention, GarbageCycle, GrownObject,
+    heap_dump, HeapDiff, HeapDump, HeapSnapshot, IntrospectionError, ObjectInfo, RetainerInfo, RetentionExplanation,
+    RootInfo, TypeDelta,
+};
+
+#[cfg(feature = "heap-introspection")]
+pub(crate) use heap::{deregister, record_garbage, record_roots, register};
+
+#[cfg(all(test, feature = "std", feature = "heap-introspection"))] // Only used in unit tests
+pub(crate) use heap::write_escaped;
+
+#[cfg(all(feature = "heap-introspection", feature = "weak-ptr"))]
+pub(crate) use heap::set_weak_metadata;
+
+/// Returns the objects reachable from `root` (which is the first returned object) and, for each one,
+/// the indexes of the objects it points to.
+fn reachable_graph(state: &State, root: NonNull<CcBox<()>>) -> (Vec<NonNull<CcBox<()>>>, Vec<Vec<usize>>) {
+    // Trace implementations expect the collector to be collecting
+    let _guard = replace_state_field!(collecting, true, state);
+
+    let mut objects = alloc::vec![root];
+    let mut indexes: BTreeMap<NonNull<CcBox<()>>, usize> = BTreeMap::new();
+    indexes.insert(root, 0);
+    let mut graph = Vec::new();
+
+    let mut edges = Vec::new();
+    // Every object is traced only once, in the order they've been discovered, so graph[i] are the edges of objects[i]
+    while let Some(&ptr) = objects.get(graph.len()) {
+        edges.clear();
+        CcBox::trace_inner(ptr, &mut Context::new(ContextInner::Introspection { edges: &mut edges }));
+
+        let object_edges = edges.iter().map(|&edge| {
+            *indexes.entry(edge).or_insert_with(|| {
+                objects.push(edge);
+                objects.len() - 1
+            })
+        }).collect();
+        graph.push(object_edges);
+    }
+
+    (objects, graph)
+}
+
+#[track_caller]
+fn assert_not_collecting(state: &State, function: &str) {
+    // Objects may have been already dropped during collections or while dropping a Cc
+    assert!(
+        !state.is_collecting() && !state.is_dropping(),
+        "{function} cannot be called while collecting"
+    );
+}
+
+/// See [`Cc::reachable_size`].
+#[track_caller]
+pub(crate) fn reachable_size(root: NonNull<CcBox<()>>) -> usize {
+    state(|state| {
+        assert_not_collecting(state, "Cc::reachable_size");
+
+        let (objects, _) = reachable_graph(state, root);
+        objects.iter().map(|ptr| unsafe { ptr.as_ref() }.layout().size()).sum()
+    })
+}
+
+/// See [`Cc::retained_size`].
+#[track_caller]
+pub(crate) fn retained_size(root: NonNull<CcBox<()>>) -> usize {
+    state(|state| {
+        assert_not_collecting(state, "Cc::retained_size");
+
+        let (objects, graph) = reachable_graph(state, root);
+
+        // Like in the counting phase of the collector, count the references coming from the retained objects
+        // (initially every reachable object) without touching the CounterMarkers
+        let mut traced_counts = alloc::vec![0u32; objects.len()];
+        for &edge in graph.iter().flatten() {
+            traced_counts[edge] += 1;
+        }
+
+        // The Cc the method has been called on is not counted, since it's the one which would be dropped
+        traced_counts[0] += 1;
+
+        // An object is not retained if it's referenced from outside the retained objects. In that case,
+        // the references it holds don't count anymore, so the objects it points to have to be checked again
+        let mut retained = alloc::vec![true; objects.len()];
+        let mut to_check: Vec<usize> = (0..objects.len()).collect();
+        while let Some(i) = to_check.pop() {
+            if retained[i] && unsafe { objects[i].as_ref() }.counter_marker().counter() > traced_counts[i] {
+                retained[i] = false;
+                for &edge in &graph[i] {
+                    traced_counts[edge] -= 1;
+                    to_check.push(edge);
+                }
+            }
+        }
+
+        objects.iter().zip(retained).filter(|(_, retained)| *retained).map(|(ptr, _)| unsafe { ptr.as_ref() }.layout().size()).sum()
+    })
+}
+
+/// Traces `ptr`, returning a strong reference to every object it points to.
+/// The returned references can't outlive `_walked`, the reference through which `ptr` is reached.
+#[track_caller]
+fn children<'a, W: ?Sized>(_walked: &'a W, ptr: NonNull<CcBox<()>>, function: &str) -> Vec<CcRef<'a, dyn Trace>> {
+    state(|state| {
+        assert_not_collecting(state, function);
+
+        let mut edges = Vec::new();
+        {
+            // Trace implementations expect the collector to be collecting
+            let _guard = replace_state_field!(collecting, true, state);
+            CcBox::trace_inner(ptr, &mut Context::new(ContextInner::Introspection { edges: &mut edges }));
+        }
+
+        // Keep every object alive, since the visitor may drop the other references to them
+        edges.into_iter().map(|edge| unsafe { CcRef::new(edge, state) }).collect()
+    })
+}
+
+/// See [`Cc::visit_edges`].
+#[track_caller]
+pub(crate) fn visit_edges<W: ?Sized>(walked: &W, ptr: NonNull<CcBox<()>>, function: &str, mut visitor: impl FnMut(CcRef<'_, dyn Trace>)) {
+    for child in children(walked, ptr, function) {
+        visitor(child);
+    }
+}
+
+/// Calls `visitor` with a [`CcRef`] to every object reachable from `cc`, including the one pointed by `cc`.
+///
+/// Objects are discovered using [`Trace`] and visited in breadth-first order. Every object is visited only once,
+/// even if it is reachable through multiple paths, and the graph is walked without recursion.
+///
+/// # Panics
+///
+/// Panics if called during a collection.
+///
+/// # Example
+/// ```rust
+///# use rust_cc::*;
+///# use rust_cc::introspection::*;
+///# use std::cell::RefCell;
+/// struct Node {
+///     value: u32,
+///     next: RefCell<Option<Cc<Node>>>,
+/// }
+///# unsafe impl Trace for Node {
+///#     fn trace(&self, ctx: &mut Context<'_>) {
+///#         self.next.trace(ctx);
+///#     }
+///# }
+///# impl Finalize for Node {}
+///
+/// let a = Cc::new(Node { value: 1, next: RefCell::new(None) });
+/// let b = Cc::new(Node { value: 2, next: RefCell::new(Some(a.clone())) });
+/// *a.next.borrow_mut() = Some(b.clone());
+///
+/// let mut sum = 0;
+/// walk_reachable(&a, |node| sum += node.downcast_ref::<Node>().unwrap().value);
+/// assert_eq!(3, sum);
+///# *a.next.borrow_mut() = None;
+/// ```
+#[track_caller]
+pub fn walk_reachable<T: ?Sized + Trace + 'static>(cc: &Cc<T>, mut visitor: impl FnMut(CcRef<'_, dyn Trace>)) {
+    let root: NonNull<CcBox<()>> = cc.inner_ptr().cast();
+
+    let mut visited = BTreeSet::new();
+    visited.insert(root);
+
+    let mut queue = VecDeque::new();
+    queue.push_back(state(|state| unsafe { CcRef::new(root, state) }));
+
+    while let Some(node) = queue.pop_front() {
+        for child in children(cc, node.ptr, "walk_reachable") {
+            if visited.insert(child.ptr) {
+                queue.push_back(child);
+            }
+        }
+
+        visitor(node);
+    }
+}
+
+/// A strong reference to an object, given to the visitors of [`Cc::visit_edges`] and [`walk_reachable`].
+///
+/// The type of the object is erased, but it can be recovered using [`downcast_ref`][`CcRef::downcast_ref`].
+/// A [`CcRef`] keeps the object alive like a [`Cc`], so it's safe to drop the other references to it while visiting.
+/// Unlike a [`Cc`], dropping a [`CcRef`] doesn't make the object a candidate for the next collection, so visiting
+/// objects doesn't change the state of the collector.
+pub struct CcRef<'a, T: ?Sized + Trace + 'static = dyn Trace> {
+    ptr: NonNull<CcBox<()>>,
+    executions: usize, // The number of executed collections when the CcRef was created
+    _phantom: PhantomData<(&'a T, Rc<()>)>, // Make CcRef !Send and !Sync
+}
+
+impl<'a, T: ?Sized + Trace + 'static> CcRef<'a, T> {
+    /// Safety: `ptr` must point to a live CcBox
+    #[inline]
+    #[track_caller]
+    unsafe fn new(ptr: NonNull<CcBox<()>>, state: &State) -> Self {
+        if ptr.as_ref().counter_marker().increment_counter().is_err() {
+            panic!("Too many references has been created to a single Cc");
+        }
+
+        CcRef {
+            ptr,
+            executions: state.executions_count(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns `true` if the two [`CcRef`]s point to the same allocation.
+    #[inline]
+    pub fn ptr_eq(this: &CcRef<'_, T>, other: &CcRef<'_, T>) -> bool {
+        this.ptr == other.ptr
+    }
+
+    /// Returns the address of the allocation.
+    #[inline]
+    pub fn address(&self) -> usize {
+        self.ptr.as_ptr() as usize
+    }
+
+    /// Returns the name of the type of the object, as returned by [`type_name`][`core::any::type_name`].
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        CcBox::type_name(self.ptr)
+    }
+
+    /// Returns the size in bytes of the allocation.
+    #[inline]
+    pub fn size(&self) -> usize {
+        unsafe { self.ptr.as_ref() }.layout().size()
+    }
+
+    /// Returns the number of [`Cc`]s to the object, not counting this [`CcRef`].
+    #[inline]
+    pub fn strong_count(&self) -> u32 {
+        unsafe { self.ptr.as_ref() }.counter_marker().counter() - 1
+    }
+
+    /// Returns a reference to the object if it is of type `U`, or [`None`] if it isn't.
+    #[inline]
+    pub fn downcast_ref<U: Trace + 'static>(&self) -> Option<&U> {
+        if CcBox::type_id(self.ptr) == TypeId::of::<U>() {
+            // SAFETY: the type of the CcBox has just been checked
+            Some(unsafe { self.ptr.cast::<CcBox<U>>().as_ref() }.get_elem())
+        } else {
+            None
+        }
+    }
+
+    /// Calls `visitor` with a [`CcRef`] to every object directly pointed by this object.
+    ///
+    /// See [`Cc::visit_edges`] for more details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called during a collection.
+    #[inline]
+    #[track_caller]
+    pub fn visit_edges(&self, visitor: impl FnMut(CcRef<'_, dyn Trace>)) {
+        visit_edges(self, self.ptr, "CcRef::visit_edges", visitor);
+    }
+}
+
+impl<'a, T: ?Sized + Trace + 'static> Drop for CcRef<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        let counter_marker = unsafe { self.ptr.as_ref() }.counter_marker();
+
+        // The object was already alive before being visited, so it doesn't have to be buffered. If a collection has been
+        // executed meanwhile, it may have been removed from POSSIBLE_CYCLES because this reference kept it alive
+        let collected = try_state(|state| state.executions_count() != self.executions).unwrap_or(true);
+        if counter_marker.counter() > 1 && !collected {
+            let res = counter_marker.decrement_counter();
+            debug_assert!(res.is_ok());
+            return;
+        }
+
+        // SAFETY: the strong reference has been acquired in CcRef::new
+        unsafe { CcBox::drop_strong(self.ptr) };
+    }
+}
+
@@ -128,26 +128,42 @@ struct Foo {
 ```"]
 //! 
 //! See the [`cleaners` module documentation][`mod@cleaners`] for more details.
+//!
+//! ## No-std support
+//!
+//! The collector state is kept inside thread locals. When the `std` feature is disabled, the `nightly` feature
+//! is required to use the unstable `#[thread_local]` attribute, which needs ELF TLS support from the target.
+//!
+//! Alternatively, the `critical-section` feature replaces the thread locals with global variables, which are always
+//! accessed inside a critical section provided by the [`critical-section`](https://crates.io/crates/critical-section) crate.
+//! This works on stable and on targets without ELF TLS, like single-core firmware. Note that:
+//!  * the global state must be enabled by calling the unsafe `init_global_state` function, whose caller guarantees
+//!    that [`Cc`]s are never used concurrently (for example, from both the main loop and interrupt handlers);
+//!  * collections run entirely inside a critical section.
 //! 
 //! [`Send`]: `std::marker::Send`
 //! [`Sync`]: `std::marker::Sync`
 //! [`Rc`]: `std::rc::Rc`
 
-#![cfg_attr(feature = "nightly", feature(unsize, coerce_unsized, ptr_metadata))]
-#![cfg_attr(all(feature = "nightly", not(feature = "std")), feature(thread_local))] // no-std related unstable features
+#![cfg_attr(feature = "nightly", feature(unsize, coerce_unsized, ptr_metadata, allocator_api))]
+#![cfg_attr(all(feature = "nightly", not(feature = "std"), not(feature = "critical-section")), feature(thread_local))] // no-std related unstable features
 #![cfg_attr(doc_auto_cfg, feature(doc_auto_cfg))]
 #![cfg_attr(not(feature = "std"), no_std)]
 
 #![deny(rustdoc::broken_intra_doc_links)]
-#![allow(clippy::thread_local_initializer_can_be_made_const)]
+#![allow(clippy::missing_const_for_thread_local)]
 #![allow(unexpected_cfgs)]
 
-#[cfg(all(not(feature = "std"), not(feature = "nightly")))]
-compile_error!("Feature \"std\" cannot be disabled without enabling feature \"nightly\" (due to #[thread_local] not being stable).");
+#[cfg(all(not(feature = "std"), not(feature = "nightly"), not(feature = "critical-section")))]
+compile_error!("Feature \"std\" cannot be disabled without enabling feature \"nightly\" (due to #[thread_local] not being stable) or feature \"critical-section\".");
 
 extern crate alloc;
 
+#[cfg(feature = "auto-collect")]
+use alloc::rc::Rc;
 use core::cell::RefCell;
+#[cfg(feature = "auto-collect")]
+use core::marker::PhantomData;
 use core::mem;
 use core::mem::ManuallyDrop;
 use core::ptr::NonNull;
@@ -163,9 +179,11 @@ struct Foo {
 #[cfg(all(test, feature = "std"))]
 mod tests;
 
+pub mod allocator;
 mod cc;
 mod counter_marker;
 mod list;
+mod report;
 pub mod state;
 mod trace;
 mod utils;
@@ -182,60 +200,415 @@ struct Foo {
 #[cfg(feature = "cleaners")]
 pub mod cleaners;
 
+#[cfg(feature = "global-stats")]
+mod global_stats;
+
+#[cfg(feature = "graph-walk")]
+pub mod introspection;
+
+#[cfg(feature = "type-stats")]
+mod type_stats;
+
+#[cfg(feature = "allocation-sites")]
+pub mod allocation_sites;
+
 #[cfg(feature = "derive")]
 pub use derives::{Finalize, Trace};
 
 pub use cc::Cc;
 pub use trace::{Context, Finalize, Trace};
 
+#[cfg(feature = "critical-section")]
+pub use utils::init_global_state;
+
+/// The default maximum number of rounds of the collection algorithm executed by a single collection.
+#[cfg(feature = "finalization")]
+const DEFAULT_FINALIZATION_ROUNDS: usize = 10;
+
+rust_cc_thread_local! {
+    // The list is wrapped in a ManuallyDrop to make POSSIBLE_CYCLES not need to be dropped. This way it
+    // remains accessible while other thread locals are being destroyed (see THREAD_EXIT_GUARD below)
+    pub(crate) static POSSIBLE_CYCLES: RefCell<ManuallyDrop<CountedList>> = const { RefCell::new(ManuallyDrop::new(CountedList::new())) };
+}
+
+#[cfg(feature = "std")]
 rust_cc_thread_local! {
-    pub(crate) static POSSIBLE_CYCLES: RefCell<CountedList> = RefCell::new(CountedList::new());
+    static THREAD_EXIT_GUARD: ThreadExitGuard = const { ThreadExitGuard };
+    static LATE_COLLECTION_GUARD: LateCollectionGuard = const { LateCollectionGuard };
+}
+
+/// Runs the last collection of the thread when it exits.
+///
+/// A destructor for [`THREAD_EXIT_GUARD`] is registered only when it's first accessed, so make sure
+/// [`register_thread_exit_guard`] is called before any [`Cc`] can be buffered into [`POSSIBLE_CYCLES`].
+#[cfg(feature = "std")]
+struct ThreadExitGuard;
+
+#[cfg(feature = "std")]
+impl Drop for ThreadExitGuard {
+    fn drop(&mut self) {
+        let _ = try_state(|state| {
+            // Panicking inside a thread local destructor aborts the program. Since this collection is implicit
+            // and may run code which previously panicked (e.g. in Trace implementations), just ignore any panic
+            let _ = std::panic::catch_unwind(|| run_collection(state::CollectionTrigger::ThreadExit));
+
+            // From now on, every Cc buffered into POSSIBLE_CYCLES will schedule another collection (see Cc::drop)
+            state.set_thread_exited(true);
+
+            if let Some(callback) = state.leak_report_callback() {
+                callback(state::LeakReport::new(state.allocated_objects(), state.allocated_bytes()));
+            }
+        });
+    }
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub(crate) fn register_thread_exit_guard() {
+    let _ = THREAD_EXIT_GUARD.try_with(|_| {});
+}
+
+/// Collects the [`Cc`]s buffered into [`POSSIBLE_CYCLES`] by the destructors of other thread locals,
+/// after the last collection of the thread has already been executed by [`ThreadExitGuard`].
+///
+/// Its destructor is registered by the first of them, so it runs after the destructor which dropped them (at least on
+/// platforms running thread local destructors in reverse order of registration, like Linux). This way, the [`Cc`]s
+/// dropped by the same destructor are collected by a single collection, instead of one collection for each of them.
+#[cfg(feature = "std")]
+struct LateCollectionGuard;
+
+#[cfg(feature = "std")]
+impl Drop for LateCollectionGuard {
+    fn drop(&mut self) {
+        // See ThreadExitGuard::drop
+        let _ = std::panic::catch_unwind(|| run_collection(state::CollectionTrigger::ThreadExit));
+    }
+}
+
+/// Schedules the collection of a [`Cc`] buffered into [`POSSIBLE_CYCLES`] after the thread's last collection.
+///
+/// Once [`LATE_COLLECTION_GUARD`] has been destroyed, every [`Cc`] buffered later is collected immediately,
+/// since there will be no other chance to collect it. Since every collection traces every object reachable from the
+/// buffered [`Cc`]s, dropping many [`Cc`]s of a big graph at this point has a cost quadratic in the graph size.
+#[cfg(feature = "std")]
+#[cold]
+pub(crate) fn schedule_late_collection() {
+    if LATE_COLLECTION_GUARD.try_with(|_| {}).is_err() {
+        run_collection(state::CollectionTrigger::ThreadExit);
+    }
 }
 
 /// Immediately executes the cycle collection algorithm and collects garbage cycles.
 ///
 /// Calling this function during a collection won't start a new collection.
-pub fn collect_cycles() {
-    let _ = try_state(|state| {
-        if state.is_collecting() {
-            return;
+///
+/// Returns a [`CollectionReport`][`state::CollectionReport`] with the statistics of the executed collection,
+/// or [`None`] if no collection has been executed. The reports of the last collections (including the automatically
+/// started ones) can be retrieved using [`state::last_collections`].
+///
+/// When the `std` feature is enabled, a last collection is also automatically executed when the thread exits.
+/// See [`state::set_leak_report_callback`] for more details.
+pub fn collect_cycles() -> Option<state::CollectionReport> {
+    run_collection(state::CollectionTrigger::Manual)
+}
+
+/// Requests the execution of a collection, even from inside a collection.
+///
+/// If no collection is running, a collection is immediately executed like [`collect_cycles`] does. Otherwise, for
+/// example when called from [`Finalize::finalize`] or from a destructor of a garbage object, the request is recorded
+/// and the collection is executed at the end of the current collection. Requests made during a collection started by
+/// a request, or from inside a collection hook, are instead executed at the next safe point, i.e. at the end of the next
+/// collection or, when the `auto-collect` feature is enabled, the next time a collection may be automatically started
+/// (unless collections are suppressed using [`no_collect`]).
+///
+/// Whether a request is still pending can be checked using [`state::is_collection_requested`]. Collections executed
+/// because of a request have [`CollectionTrigger::Requested`][`state::CollectionTrigger::Requested`] as trigger.
+///
+/// # Example
+#[cfg_attr(
+    feature = "finalization",
+    doc = r"```rust"
+)]
+#[cfg_attr(
+    not(feature = "finalization"),
+    doc = r"```rust,ignore"
+)]
+///# use rust_cc::*;
+///# use rust_cc::state::*;
+///# use std::cell::RefCell;
+/// struct Releaser {
+///     cycle: RefCell<Option<Cc<Releaser>>>,
+/// }
+///# unsafe impl Trace for Releaser {
+///#     fn trace(&self, ctx: &mut Context<'_>) {
+///#         self.cycle.trace(ctx);
+///#     }
+///# }
+///
+/// impl Finalize for Releaser {
+///     fn finalize(&self) {
+///         // Release a big graph here, then make sure it gets collected
+///         assert_eq!(CollectionRequest::Pending, request_collection().unwrap());
+///     }
+/// }
+///
+/// let releaser = Cc::new(Releaser { cycle: RefCell::new(None) });
+/// *releaser.cycle.borrow_mut() = Some(releaser.clone());
+/// drop(releaser);
+///
+/// collect_cycles();
+/// assert!(!is_collection_requested().unwrap());
+/// assert_eq!(CollectionTrigger::Requested, last_collections().unwrap().last().unwrap().trigger());
+/// ```
+pub fn request_collection() -> Result<state::CollectionRequest, state::StateAccessError> {
+    try_state(|state| {
+        if state.is_collecting() || state.is_running_hook() {
+            state.set_collection_requested(true);
+            return state::CollectionRequest::Pending;
         }
 
-        let _ = POSSIBLE_CYCLES.try_with(|pc| {
-            collect(state, pc);
-        });
+        match run_collection(state::CollectionTrigger::Requested) {
+            Some(report) => state::CollectionRequest::Executed(report),
+            None => {
+                // The collection couldn't be started, try again at the next safe point
+                state.set_collection_requested(true);
+                state::CollectionRequest::Pending
+            },
+        }
+    })
+}
+
+pub(crate) fn run_collection(trigger: state::CollectionTrigger) -> Option<state::CollectionReport> {
+    try_state(|state| {
+        if state.is_collecting() || state.is_running_hook() {
+            return None;
+        }
+
+        let report = POSSIBLE_CYCLES.try_with(|pc| {
+            collect(state, pc, trigger)
+        }).ok();
 
         #[cfg(feature = "auto-collect")]
         adjust_trigger_point(state);
-    });
+
+        report
+    }).ok().flatten()
 }
 
+/// Starts a collection if needed and makes sure that `size` bytes can be allocated without exceeding the heap limit.
+///
+/// The heap limit is read in the same configuration access used to check whether a collection should be started.
 #[cfg(feature = "auto-collect")]
 #[inline(never)]
-pub(crate) fn trigger_collection() {
-    let _ = try_state(|state| {
-        if state.is_collecting() {
+pub(crate) fn trigger_collection(state: &State, size: usize) -> Result<(), config::HeapLimitError> {
+    let limit = POSSIBLE_CYCLES.try_with(|pc| {
+        let can_collect = !state.is_collecting() && !state.is_running_hook();
+        let suppressed = state.are_collections_suppressed();
+
+        // Execute the collection requested when it couldn't be started (see request_collection)
+        let requested = can_collect && !suppressed && state.is_collection_requested();
+        let check_policy = can_collect && !requested && !(suppressed && state.is_collection_deferred());
+
+        let (trigger, limit) = config::config(|config| {
+            let trigger = if check_policy { config.should_collect(state, pc) } else { None };
+            (trigger, config.max_heap_bytes().map(|limit| (limit, config.out_of_memory_policy())))
+        }).unwrap_or((None, None));
+
+        if requested {
+            collect(state, pc, state::CollectionTrigger::Requested);
+
+            adjust_trigger_point(state);
+        } else if let Some(trigger) = trigger {
+            if suppressed {
+                state.defer_collection(trigger);
+            } else {
+                collect(state, pc, trigger);
+
+                adjust_trigger_point(state);
+            }
+        }
+
+        limit
+    }).ok().flatten();
+
+    let Some((limit, policy)) = limit else {
+        return Ok(());
+    };
+
+    if state.memory_pressure().saturating_add(size) <= limit {
+        return Ok(());
+    }
+
+    heap_limit_exceeded(state, size, limit, policy)
+}
+
+/// Executes `f` without automatically starting any collection.
+///
+/// This is useful to avoid running finalizers and destructors of garbage objects while holding [`RefCell`] borrows
+/// or other state that they may try to access. Calls can be nested. Collections started by [`collect_cycles`] are still executed.
+///
+/// A collection which became due while executing `f` is run when the outermost suppression ends or
+/// is left to the next automatic trigger, as set using [`Config::set_deferred_collection`][`config::Config::set_deferred_collection`].
+///
+/// Note that the [heap limit][`config::Config::set_max_heap_bytes`] is still enforced, but without forcing a collection first.
+///
+/// # Example
+/// ```rust
+///# use rust_cc::*;
+///# use std::cell::RefCell;
+/// let cache: RefCell<Vec<Cc<u32>>> = RefCell::new(Vec::new());
+///
+/// no_collect(|| {
+///     let mut cache = cache.borrow_mut();
+///     for i in 0..1000 {
+///         cache.push(Cc::new(i)); // Never starts a collection
+///     }
+/// });
+/// ```
+#[cfg(feature = "auto-collect")]
+pub fn no_collect<R>(f: impl FnOnce() -> R) -> R {
+    let _guard = NoCollectGuard::new();
+    f()
+    // _guard is dropped here, possibly starting the deferred collection
+}
+
+/// A guard which suppresses automatic collections while alive.
+///
+/// See [`no_collect`] for more details.
+///
+/// # Example
+/// ```rust
+///# use rust_cc::*;
+/// let guard = NoCollectGuard::new();
+/// let _ = Cc::new(5); // Never starts a collection
+/// drop(guard);
+/// ```
+#[cfg(feature = "auto-collect")]
+#[must_use = "collections are suppressed only while the guard is alive"]
+pub struct NoCollectGuard {
+    suppressed: bool, // false if the state couldn't be accessed when the guard was created
+    _phantom: PhantomData<Rc<()>>, // Make NoCollectGuard !Send and !Sync
+}
+
+#[cfg(feature = "auto-collect")]
+impl NoCollectGuard {
+    /// Suppresses automatic collections until the returned guard is dropped.
+    #[inline]
+    pub fn new() -> NoCollectGuard {
+        NoCollectGuard {
+            suppressed: try_state(|state| state.suppress_collections()).is_ok(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "auto-collect")]
+impl Default for NoCollectGuard {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "auto-collect")]
+impl Drop for NoCollectGuard {
+    fn drop(&mut self) {
+        if !self.suppressed {
             return;
         }
 
-        let _ = POSSIBLE_CYCLES.try_with(|pc| {
-            if config::config(|config| config.should_collect(state, pc)).unwrap_or(false) {
-                collect(state, pc);
+        let _ = try_state(|state| {
+            if !state.unsuppress_collections() {
+                return; // Still suppressed by an outer guard
+            }
 
-                adjust_trigger_point(state);
+            let Some(trigger) = state.take_deferred_collection() else {
+                return;
+            };
+
+            // Don't risk panicking again while already panicking, the next trigger will start the collection
+            #[cfg(feature = "std")]
+            if std::thread::panicking() {
+                return;
+            }
+
+            let run = config::config(|config| config.deferred_collection() == config::DeferredCollection::RunImmediately);
+            if !matches!(run, Ok(true)) || state.is_collecting() || state.is_running_hook() {
+                return;
             }
+
+            let _ = POSSIBLE_CYCLES.try_with(|pc| {
+                collect(state, pc, trigger);
+
+                adjust_trigger_point(state);
+            });
         });
-    });
+    }
+}
+
+#[cfg(feature = "auto-collect")]
+#[cold]
+#[inline(never)]
+fn heap_limit_exceeded(state: &State, size: usize, limit: usize, policy: config::OutOfMemoryPolicy) -> Result<(), config::HeapLimitError> {
+    let check = |state: &State| {
+        let allocated = state.memory_pressure();
+        if allocated.saturating_add(size) <= limit {
+            Ok(())
+        } else {
+            Err(config::HeapLimitError::new(size, allocated, limit))
+        }
+    };
+
+    // Force a collection first. No collection is started if one is already running or if collections are suppressed
+    if !state.are_collections_suppressed() {
+        run_collection(state::CollectionTrigger::HeapLimit);
+    }
+
+    let error = match check(state) {
+        Ok(()) => return Ok(()),
+        Err(error) => error,
+    };
+
+    match policy {
+        config::OutOfMemoryPolicy::Panic => panic!("{}", error),
+        config::OutOfMemoryPolicy::Callback(callback) => {
+            callback(error);
+            check(state)
+        },
+        config::OutOfMemoryPolicy::Error => Err(error),
+    }
 }
 
 #[cfg(feature = "auto-collect")]
 fn adjust_trigger_point(state: &State) {
-    let _ = config::config(|config| config.adjust(state));
+    let _ = POSSIBLE_CYCLES.try_with(|pc| {
+        let _ = config::config(|config| config.adjust(state, pc));
+    });
 }
 
-fn collect(state: &State, possible_cycles: &RefCell<CountedList>) {
+/// Calls `hook`, unless another hook is already running.
+#[inline]
+fn run_hook(state: &State, hook: impl FnOnce()) {
+    if state.is_running_hook() {
+        return;
+    }
+
+    let _running_hook_guard = replace_state_field!(running_hook, true, state);
+    hook();
+    // _running_hook_guard is dropped here, resetting state.running_hook
+}
+
+fn collect(state: &State, possible_cycles: &RefCell<ManuallyDrop<CountedList>>, trigger: state::CollectionTrigger) -> state::CollectionReport {
+    if let Some(hook) = state.collection_start_hook() {
+        run_hook(state, || hook(trigger));
+    }
+
     state.set_collecting(true);
     state.increment_executions_count();
+    state.set_collection_requested(false); // This collection satisfies the previous requests
+
+    #[cfg(feature = "std")]
+    let start = std::time::Instant::now();
 
     struct DropGuard<'a> {
         state: &'a State,
@@ -245,14 +618,37 @@ impl<'a> Drop for DropGuard<'a> {
         #[inline]
         fn drop(&mut self) {
             self.state.set_collecting(false);
+
+            // Don't count the objects allocated by finalizers and destructors during the collection
+            self.state.reset_allocations_since_last_collection();
         }
     }
 
-    let _drop_guard = DropGuard { state };
+    let drop_guard = DropGuard { state };
+
+    // Discard the report of a previous collection which panicked
+    let _ = state.take_report();
+    state.update_report(|report| report.trigger = trigger);
+
+    // The number of objects put back into POSSIBLE_CYCLES after being finalized and not yet checked again.
+    // Objects put back by an interrupted collection are checked by the next one
+    #[cfg(feature = "finalization")]
+    let mut put_back = state.take_put_back();
+    #[cfg(not(feature = "finalization"))]
+    let mut put_back = 0usize;
+
+    // The number of objects which can still be traced without exceeding the maximum pause, None if there's no limit
+    #[cfg(all(feature = "auto-collect", feature = "std"))]
+    let mut max_traced = pause_target(state, trigger).and_then(|pause| state.traced_objects_within(pause));
+    #[cfg(not(all(feature = "auto-collect", feature = "std")))]
+    let mut max_traced = None;
+
+    #[cfg(feature = "finalization")]
+    let (max_rounds, drop_leftovers) = finalization_rounds();
 
     #[cfg(feature = "finalization")]
-    for _ in 0..10 {
-        // Limit to 10 executions. A collection usually completes in 2 executions, so passing
+    for _ in 0..max_rounds {
+        // Limit the executions (to 10 by default). A collection usually completes in 2 executions, so passing
         // 10 and still having objects to clean up and finalize almost surely means that some
         // finalizer is doing something weird, like the following:
         //
@@ -268,34 +664,158 @@ fn drop(&mut self) {
         //
         // Thus, it is fine to just leave the remaining objects into POSSIBLE_CYCLES for the
         // next collection execution. The program has already been stopped for too much time.
+        // The finalization policy may instead require to drop the remaining garbage without finalizing it.
 
-        if is_empty(possible_cycles) {
+        if is_empty(possible_cycles) || max_traced == Some(0) {
             break;
         }
 
-        __collect(state, possible_cycles);
+        __collect(state, possible_cycles, trigger, &mut put_back, &mut max_traced, true);
+    }
+    #[cfg(feature = "finalization")]
+    if drop_leftovers && !is_empty(possible_cycles) && max_traced != Some(0) {
+        __collect(state, possible_cycles, trigger, &mut put_back, &mut max_traced, false);
     }
     #[cfg(not(feature = "finalization"))]
     if !is_empty(possible_cycles) {
-        __collect(state, possible_cycles);
+        __collect(state, possible_cycles, trigger, &mut put_back, &mut max_traced);
     }
 
-    // _drop_guard is dropped here, setting state.collecting to false
+    #[cfg(feature = "finalization")]
+    state.set_put_back(put_back);
+
+    #[cfg(all(feature = "auto-collect", feature = "std"))]
+    {
+        let interrupted = max_traced == Some(0) && !is_empty(possible_cycles);
+        state.set_collection_interrupted(interrupted);
+        state.update_report(|report| report.interrupted = interrupted);
+    }
+
+    #[cfg(feature = "global-stats")]
+    if let Ok(pc) = possible_cycles.try_borrow() {
+        global_stats::record_buffered_objects_count(pc.size());
+    }
+
+    state.record_live_bytes();
+
+    #[allow(unused_mut)] // Only mutated when std is enabled
+    let mut report = state.take_report();
+
+    #[cfg(feature = "std")]
+    {
+        let end = std::time::Instant::now();
+        report.duration = end - start;
+        state.set_last_collection_end(end);
+
+        #[cfg(feature = "auto-collect")]
+        state.record_traced_object_cost(report.duration, report.traced_counting);
+    }
+
+    state.push_report(report);
+
+    drop(drop_guard); // Set state.collecting to false before calling the hook
+
+    if let Some(hook) = state.collection_end_hook() {
+        run_hook(state, || hook(trigger, &report));
+    }
+
+    // Execute the collection requested during this collection, unless this collection has already been requested.
+    // This avoids executing collections forever if finalizers always request a new collection
+    if state.is_collection_requested() && trigger != state::CollectionTrigger::Requested && !state.is_running_hook() {
+        collect(state, possible_cycles, state::CollectionTrigger::Requested);
+    }
+
+    report
+}
+
+/// Returns the maximum number of rounds of the collection algorithm and whether
+/// the garbage left after the last round should be dropped without finalizing it.
+#[cfg(feature = "finalization")]
+fn finalization_rounds() -> (usize, bool) {
+    #[cfg(feature = "auto-collect")]
+    if let Ok(policy) = config::config(|config| config.finalization_policy()) {
+        return (policy.max_rounds(), policy.leftover_objects() == config::LeftoverObjects::DropWithoutFinalizing);
+    }
+
+    (DEFAULT_FINALIZATION_ROUNDS, false)
 }
 
-fn __collect(state: &State, possible_cycles: &RefCell<CountedList>) {
+/// Returns the maximum pause of the collection, or [`None`] if the collection shouldn't be interrupted.
+#[cfg(all(feature = "auto-collect", feature = "std"))]
+fn pause_target(state: &State, trigger: state::CollectionTrigger) -> Option<std::time::Duration> {
+    use state::CollectionTrigger as Trigger;
+
+    // Only automatic collections are limited. Heap limit collections must free as much memory as possible
+    if !matches!(trigger, Trigger::BytesThreshold | Trigger::BufferedThreshold | Trigger::Policy | Trigger::Resumed) {
+        return None;
+    }
+
+    let pause = config::config(|config| config.max_pause()).ok().flatten();
+    state.update_report(|report| report.pause_target = pause);
+    pause
+}
+
+/// `put_back` is the number of objects put back into POSSIBLE_CYCLES after being finalized which haven't been checked
+/// again yet. It's updated with the number of objects not checked by this iteration and the ones put back by it.
+///
+/// `max_traced` is the number of objects which can be traced before leaving the remaining candidates into
+/// POSSIBLE_CYCLES, or [`None`] if there's no limit. It's decreased by the number of objects traced by this iteration.
+///
+/// If `finalize` is `false`, the garbage objects found are dropped without finalizing them.
+fn __collect(
+    state: &State,
+    possible_cycles: &RefCell<ManuallyDrop<CountedList>>,
+    trigger: state::CollectionTrigger,
+    put_back: &mut usize,
+    max_traced: &mut Option<usize>,
+    #[cfg(feature = "finalization")] finalize: bool,
+) {
+    state.update_report(|report| report.iterations += 1);
+
     let mut non_root_list = List::new();
+    #[cfg(feature = "finalization")]
+    let complete; // Whether every candidate has been processed
     {
         let mut root_list = List::new();
+        let buffered = possible_cycles.borrow().size();
+        let mut traced_counting = 0usize;
 
         while let Some(ptr) = get_and_remove_first(possible_cycles) {
             // remove_first already marks ptr as NonMarked
-            trace_counting(ptr, &mut root_list, &mut non_root_list);
+            traced_counting += trace_counting(ptr, &mut root_list, &mut non_root_list);
+
+            // At least one candidate is always processed, so that interrupted collections make progress
+            if max_traced.is_some_and(|max| traced_counting >= max) {
+                break;
+            }
         }
 
-        trace_roots(root_list, &mut non_root_list);
+        // Candidates not processed are left into POSSIBLE_CYCLES for the next collection
+        let candidates = buffered - possible_cycles.borrow().size();
+        #[cfg(feature = "finalization")]
+        {
+            complete = candidates == buffered;
+        }
+        if let Some(max) = max_traced {
+            *max = max.saturating_sub(traced_counting);
+        }
+
+        #[cfg(feature = "heap-introspection")]
+        introspection::record_roots(state, &root_list);
+
+        let (traced_roots, roots) = trace_roots(root_list, &mut non_root_list);
+
+        state.update_report(|report| {
+            report.candidates += candidates;
+            report.traced_counting += traced_counting;
+            report.traced_roots += traced_roots;
+            report.roots += roots;
+        });
     }
 
+    #[cfg(not(feature = "finalization"))]
+    let _ = put_back; // Objects are never put back without finalization
+
     if !non_root_list.is_empty() {
         #[cfg(feature = "pedantic-debug-assertions")]
         non_root_list.iter().for_each(|ptr| {
@@ -310,22 +830,43 @@ fn __collect(state: &State, possible_cycles: &RefCell<CountedList>) {
 
         #[cfg(feature = "finalization")]
         {
-            let has_finalized: bool;
+            let mut finalized = 0usize;
+            let mut refound = 0usize; // The objects already finalized which are still garbage
             let mut non_root_list_size = 0usize; // Counting the size of non_root only now since it is required by mark_self_and_append
             {
                 let _finalizing_guard = replace_state_field!(finalizing, true, state);
 
-                has_finalized = non_root_list.iter().fold(false, |has_finalized, ptr| {
+                if let Some(hook) = state.finalize_phase_hook().filter(|_| finalize) {
+                    run_hook(state, || hook(trigger));
+                }
+
+                non_root_list.iter().for_each(|ptr| {
                     non_root_list_size += 1;
-                    CcBox::finalize_inner(ptr.cast()) || has_finalized
+                    if !unsafe { ptr.as_ref() }.counter_marker().needs_finalization() {
+                        refound += 1;
+                    } else if finalize && CcBox::finalize_inner(ptr.cast()) {
+                        finalized += 1;
+                    }
                 });
 
                 // _finalizing_guard is dropped here, resetting state.finalizing
             }
 
-            if !has_finalized {
-                deallocate_list(non_root_list, state);
+            // The objects put back which aren't garbage anymore have been resurrected. If some candidates haven't
+            // been processed, the put back objects may not have been checked yet, so they're counted later
+            let not_refound = put_back.saturating_sub(refound);
+            let resurrected = if complete { not_refound } else { 0 };
+            *put_back = not_refound - resurrected;
+            state.update_report(|report| {
+                report.finalized += finalized;
+                report.resurrected += resurrected;
+            });
+
+            if finalized == 0 {
+                deallocate_list(non_root_list, state, trigger);
             } else {
+                *put_back += non_root_list_size;
+
                 // Put CcBoxes back into the possible cycles list. They will be re-processed in the
                 // next iteration of the loop, which will automatically check for resurrected objects
                 // using the same algorithm of the initial tracing. This makes it more difficult to
@@ -355,23 +896,32 @@ fn __collect(state: &State, possible_cycles: &RefCell<CountedList>) {
 
         #[cfg(not(feature = "finalization"))]
         {
-            deallocate_list(non_root_list, state);
+            deallocate_list(non_root_list, state, trigger);
+        }
+    } else {
+        #[cfg(feature = "finalization")]
+        {
+            // Every object put back has been resurrected, unless some candidates haven't been processed
+            if complete {
+                let resurrected = mem::take(put_back);
+                state.update_report(|report| report.resurrected += resurrected);
+            }
         }
     }
 }
 
 #[inline]
-fn is_empty(list: &RefCell<CountedList>) -> bool {
+fn is_empty(list: &RefCell<ManuallyDrop<CountedList>>) -> bool {
     list.borrow().is_empty()
 }
 
 #[inline]
-fn get_and_remove_first(list: &RefCell<CountedList>) -> Option<NonNull<CcBox<()>>> {
+fn get_and_remove_first(list: &RefCell<ManuallyDrop<CountedList>>) -> Option<NonNull<CcBox<()>>> {
     list.borrow_mut().remove_first()
 }
 
 #[inline]
-fn deallocate_list(to_deallocate_list: List, state: &State) {
+fn deallocate_list(to_deallocate_list: List, state: &State, trigger: state::CollectionTrigger) {
     /// Just a wrapper used to handle the dropping of to_deallocate_list.
     /// When dropped, the objects inside are set as dropped
     struct ToDropList {
@@ -412,15 +962,25 @@ fn drop(&mut self) {
         }
     }
 
+    #[cfg(feature = "heap-introspection")]
+    introspection::record_garbage(state, &to_deallocate_list);
+
     let _dropping_guard = replace_state_field!(dropping, true, state);
 
+    if let Some(hook) = state.drop_phase_hook() {
+        run_hook(state, || hook(trigger));
+    }
+
     // Redefine to_deallocate_list with the ToDropList wrapper
     let to_deallocate_list = ToDropList {
         list: ManuallyDrop::new(to_deallocate_list),
     };
 
     // Drop every CcBox before deallocating them (see comment below)
+    let mut dropped = 0usize;
     to_deallocate_list.iter().for_each(|ptr| {
+        dropped += 1;
+
         // SAFETY: ptr is valid to access and drop in place
         unsafe {
             debug_assert!(ptr.as_ref().counter_marker().is_traced());
@@ -437,6 +997,7 @@ fn drop(&mut self) {
 
         // Don't deallocate now since next drop_inner calls will probably access this object while executing drop glues
     });
+    state.update_report(|report| report.dropped += dropped);
 
     // Don't drop the list now if a panic happens
     // No panic should ever happen, however cc_dealloc could in theory panic if state is not accessible
@@ -454,6 +1015,8 @@ fn drop(&mut self) {
         //         and then the allocation gets deallocated immediately after.
         unsafe {
             let layout = ptr.as_ref().layout();
+            #[cfg(feature = "type-stats")]
+            type_stats::record_collected(ptr);
             cc_dealloc(ptr, layout, state);
         }
     });
@@ -461,24 +1024,33 @@ fn drop(&mut self) {
     // _dropping_guard is dropped here, resetting state.dropping
 }
 
+/// Returns the number of traced objects.
 fn trace_counting(
     ptr: NonNull<CcBox<()>>,
     root_list: &mut List,
     non_root_list: &mut List,
-) {
+) -> usize {
     let mut ctx = Context::new(ContextInner::Counting {
         root_list,
         non_root_list,
     });
 
     CcBox::start_tracing(ptr, &mut ctx);
+    ctx.traced()
 }
 
-fn trace_roots(mut root_list: List, non_root_list: &mut List) {
+/// Returns the number of traced objects and the number of roots found.
+fn trace_roots(mut root_list: List, non_root_list: &mut List) -> (usize, usize) {
+    let mut traced = 0;
+    let mut roots = 0;
+
     while let Some(ptr) = root_list.remove_first() {
         let mut ctx = Context::new(ContextInner::RootTracing { non_root_list, root_list: &mut root_list });
         CcBox::start_tracing(ptr, &mut ctx);
+        traced += ctx.traced();
+        roots += ctx.roots();
     }
 
     mem::forget(root_list); // root_list is empty, no need run List::drop
+    (traced, roots)
 }
@@ -22,7 +22,7 @@ fn contains(&self, ptr: NonNull<CcBox<()>>) -> bool {
         self.iter().any(|elem| elem == ptr)
     }
 
-    fn iter(&self) -> Iter;
+    fn iter(&self) -> Iter<'_>;
 
     #[cfg(all(test, feature = "std"))] // Only used in unit tests
     fn into_iter(self) -> ListIter<Self>;
@@ -130,7 +130,7 @@ fn is_empty(&self) -> bool {
     }
 
     #[inline]
-    fn iter(&self) -> Iter {
+    fn iter(&self) -> Iter<'_> {
         self.into_iter()
     }
 
@@ -312,7 +312,7 @@ fn contains(&self, ptr: NonNull<CcBox<()>>) -> bool {
     }
 
     #[inline]
-    fn iter(&self) -> Iter {
+    fn iter(&self) -> Iter<'_> {
         self.list.iter()
     }
 
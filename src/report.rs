@@ -0,0 +1,276 @@
+//! Statistics about the executed collections.
+
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+/// The number of collections kept in the history returned by [`last_collections`][`fn@crate::state::last_collections`].
+pub(crate) const HISTORY_LEN: usize = 16;
+
+/// Statistics about a single collection.
+///
+/// When finalization is enabled, a collection may execute the collection algorithm more than once
+/// (see [`iterations`][`CollectionReport::iterations`]). In this case, the counts are summed over every iteration.
+///
+/// A report is returned by [`collect_cycles`][`fn@crate::collect_cycles`] and the reports of the last collections
+/// can be retrieved using [`last_collections`][`fn@crate::state::last_collections`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CollectionReport {
+    pub(crate) trigger: CollectionTrigger,
+    pub(crate) candidates: usize,
+    pub(crate) traced_counting: usize,
+    pub(crate) traced_roots: usize,
+    pub(crate) roots: usize,
+    #[cfg(feature = "finalization")]
+    pub(crate) finalized: usize,
+    #[cfg(feature = "finalization")]
+    pub(crate) resurrected: usize,
+    pub(crate) dropped: usize,
+    pub(crate) freed: usize,
+    pub(crate) freed_bytes: usize,
+    pub(crate) iterations: usize,
+    #[cfg(feature = "std")]
+    pub(crate) duration: Duration,
+    #[cfg(all(feature = "auto-collect", feature = "std"))]
+    pub(crate) pause_target: Option<Duration>,
+    #[cfg(all(feature = "auto-collect", feature = "std"))]
+    pub(crate) interrupted: bool,
+}
+
+impl CollectionReport {
+    #[inline]
+    pub(crate) const fn new() -> Self {
+        Self {
+            trigger: CollectionTrigger::Manual,
+            candidates: 0,
+            traced_counting: 0,
+            traced_roots: 0,
+            roots: 0,
+            #[cfg(feature = "finalization")]
+            finalized: 0,
+            #[cfg(feature = "finalization")]
+            resurrected: 0,
+            dropped: 0,
+            freed: 0,
+            freed_bytes: 0,
+            iterations: 0,
+            #[cfg(feature = "std")]
+            duration: Duration::ZERO,
+            #[cfg(all(feature = "auto-collect", feature = "std"))]
+            pause_target: None,
+            #[cfg(all(feature = "auto-collect", feature = "std"))]
+            interrupted: false,
+        }
+    }
+
+    /// Returns what started the collection.
+    #[inline]
+    pub fn trigger(&self) -> CollectionTrigger {
+        self.trigger
+    }
+
+    /// Returns the number of objects taken from the buffer of possible cycles.
+    ///
+    /// See [`buffered_objects_count`][`fn@crate::state::buffered_objects_count`] for more details.
+    #[inline]
+    pub fn candidates(&self) -> usize {
+        self.candidates
+    }
+
+    /// Returns the number of objects traced while counting the references coming from other traced objects.
+    #[inline]
+    pub fn traced_counting(&self) -> usize {
+        self.traced_counting
+    }
+
+    /// Returns the number of objects traced while searching the objects reachable from the roots, i.e. the number of
+    /// traced objects which have been found alive.
+    #[inline]
+    pub fn traced_roots(&self) -> usize {
+        self.traced_roots
+    }
+
+    /// Returns the number of roots found, i.e. the number of traced objects which are referenced from outside the traced objects.
+    #[inline]
+    pub fn roots(&self) -> usize {
+        self.roots
+    }
+
+    /// Returns the number of finalized objects.
+    #[cfg(feature = "finalization")]
+    #[inline]
+    pub fn finalized(&self) -> usize {
+        self.finalized
+    }
+
+    /// Returns the number of finalized objects which have been found alive again after their finalization.
+    ///
+    /// This is an estimate, since objects which became garbage during finalization are counted as
+    /// the finalized objects which are still garbage.
+    ///
+    /// Objects finalized by an [interrupted][`CollectionReport::interrupted`] collection are counted by the
+    /// collection which checks them again.
+    #[cfg(feature = "finalization")]
+    #[inline]
+    pub fn resurrected(&self) -> usize {
+        self.resurrected
+    }
+
+    /// Returns the number of garbage objects found and dropped by the collector.
+    #[inline]
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Returns the number of deallocated objects.
+    ///
+    /// This also includes the objects which have been deallocated because they were only
+    /// referenced by the [`dropped`][`CollectionReport::dropped`] objects.
+    #[inline]
+    pub fn freed(&self) -> usize {
+        self.freed
+    }
+
+    /// Returns the number of bytes deallocated.
+    #[inline]
+    pub fn freed_bytes(&self) -> usize {
+        self.freed_bytes
+    }
+
+    /// Returns the number of iterations of the collection algorithm.
+    ///
+    /// When finalization is enabled, the algorithm is re-executed after finalizing objects to detect resurrected objects.
+    #[inline]
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// Returns the time taken by the collection.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns the maximum pause the collection tried to respect, or [`None`] if the collection wasn't limited.
+    ///
+    /// The achieved pause is returned by [`duration`][`CollectionReport::duration`].
+    /// See [`Config::set_max_pause`][`crate::config::Config::set_max_pause`] for more details.
+    #[cfg(all(feature = "auto-collect", feature = "std"))]
+    #[inline]
+    pub fn pause_target(&self) -> Option<Duration> {
+        self.pause_target
+    }
+
+    /// Returns whether the collection has been interrupted to respect the [`pause_target`][`CollectionReport::pause_target`].
+    ///
+    /// The objects left in the buffer of possible cycles are processed by the next collections,
+    /// which have [`CollectionTrigger::Resumed`] as trigger.
+    #[cfg(all(feature = "auto-collect", feature = "std"))]
+    #[inline]
+    pub fn interrupted(&self) -> bool {
+        self.interrupted
+    }
+}
+
+/// What started a collection.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CollectionTrigger {
+    /// The collection has been started by [`collect_cycles`][`fn@crate::collect_cycles`].
+    #[default]
+    Manual,
+
+    /// The collection has been automatically started since the allocated bytes exceeded the threshold.
+    ///
+    /// See the [`config` module documentation][`mod@crate::config`] for more details.
+    #[cfg(feature = "auto-collect")]
+    BytesThreshold,
+
+    /// The collection has been automatically started since the buffered objects exceeded the threshold.
+    ///
+    /// See the [`config` module documentation][`mod@crate::config`] for more details.
+    #[cfg(feature = "auto-collect")]
+    BufferedThreshold,
+
+    /// The collection has been automatically started by a custom [`CollectionPolicy`][`crate::config::CollectionPolicy`].
+    ///
+    /// See [`Config::set_policy`][`crate::config::Config::set_policy`] for more details.
+    #[cfg(feature = "auto-collect")]
+    Policy,
+
+    /// The collection has been forced since a new allocation would have exceeded the heap limit.
+    ///
+    /// See [`Config::set_max_heap_bytes`][`crate::config::Config::set_max_heap_bytes`] for more details.
+    #[cfg(feature = "auto-collect")]
+    HeapLimit,
+
+    /// The collection resumes the work left by a previous collection, which has been interrupted to respect the maximum pause.
+    ///
+    /// See [`Config::set_max_pause`][`crate::config::Config::set_max_pause`] for more details.
+    #[cfg(all(feature = "auto-collect", feature = "std"))]
+    Resumed,
+
+    /// The collection has been started by [`request_collection`][`fn@crate::request_collection`].
+    Requested,
+
+    /// A collection executed when the thread exits.
+    ///
+    /// See [`set_leak_report_callback`][`fn@crate::state::set_leak_report_callback`] for more details.
+    #[cfg(feature = "std")]
+    ThreadExit,
+}
+
+/// The result of [`request_collection`][`fn@crate::request_collection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollectionRequest {
+    /// The collection has been executed immediately.
+    Executed(CollectionReport),
+    /// A collection is already running, so the requested collection will be executed at the end of it
+    /// or at the next safe point. See [`request_collection`][`fn@crate::request_collection`] for more details.
+    Pending,
+}
+
+/// A fixed-size ring buffer of the last [`HISTORY_LEN`] reports.
+pub(crate) struct ReportHistory {
+    reports: [CollectionReport; HISTORY_LEN],
+    next: usize,
+    len: usize,
+}
+
+impl ReportHistory {
+    #[inline]
+    pub(crate) const fn new() -> Self {
+        Self {
+            reports: [CollectionReport::new(); HISTORY_LEN],
+            next: 0,
+            len: 0,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn push(&mut self, report: CollectionReport) {
+        self.reports[self.next] = report;
+        self.next = (self.next + 1) % HISTORY_LEN;
+        self.len = usize::min(self.len + 1, HISTORY_LEN);
+    }
+
+    /// Returns the newest report.
+    #[cfg(feature = "auto-collect")]
+    #[inline]
+    pub(crate) fn last(&self) -> Option<CollectionReport> {
+        (self.len != 0).then(|| self.reports[(self.next + HISTORY_LEN - 1) % HISTORY_LEN])
+    }
+
+    /// Returns the reports from the oldest to the newest.
+    pub(crate) fn to_vec(&self) -> Vec<CollectionReport> {
+        let start = (self.next + HISTORY_LEN - self.len) % HISTORY_LEN;
+        (0..self.len).map(|i| self.reports[(start + i) % HISTORY_LEN]).collect()
+    }
+
+    #[cfg(all(test, feature = "std"))] // Only used in unit tests
+    pub(crate) fn clear(&mut self) {
+        self.next = 0;
+        self.len = 0;
+    }
+}
@@ -2,11 +2,33 @@
 
 use alloc::alloc::Layout;
 use alloc::rc::Rc;
-use core::cell::Cell;
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
 use core::marker::PhantomData;
+#[cfg(feature = "std")]
+use std::time::Instant;
+#[cfg(all(feature = "auto-collect", feature = "std"))]
+use std::time::Duration;
+#[cfg(feature = "heap-introspection")]
+use core::mem::ManuallyDrop;
 use thiserror::Error;
+use crate::allocator::CcAllocator;
+use crate::report::ReportHistory;
+#[cfg(feature = "heap-introspection")]
+use crate::introspection::CollectionDiagnostics;
 use crate::utils;
 
+pub use crate::report::{CollectionReport, CollectionRequest, CollectionTrigger};
+
+#[cfg(feature = "global-stats")]
+pub use crate::global_stats::{global_stats, GlobalStats, StatsSnapshot, ThreadStats};
+
+#[cfg(feature = "heap-introspection")]
+pub use crate::introspection::explain_retention;
+
+#[cfg(feature = "type-stats")]
+pub use crate::type_stats::{type_stats, TypeStats};
+
 utils::rust_cc_thread_local! {
     static STATE: State = const { State::new() };
 }
@@ -37,23 +59,102 @@ pub(crate) fn reset_state() {
         state.collecting.set(false);
 
         #[cfg(feature = "finalization")]
-        state.finalizing.set(false);
+        {
+            state.finalizing.set(false);
+            state.put_back.set(0);
+        }
 
         state.dropping.set(false);
         state.allocated_bytes.set(0);
+        state.external_bytes.set(0);
+        state.allocated_objects.set(0);
+        state.allocations_since_last_collection.set(0);
+        state.live_bytes_after_collection.set(0);
         state.executions_counter.set(0);
+        state.allocator.set(None);
+        state.allocator_allocations.set(0);
+        state.current_report.set(CollectionReport::new());
+        state.report_history.borrow_mut().clear();
+        state.running_hook.set(false);
+        state.collection_requested.set(false);
+        #[cfg(feature = "auto-collect")]
+        {
+            state.no_collect_depth.set(0);
+            state.deferred_collection.set(None);
+        }
+        state.collection_start_hook.set(None);
+        state.collection_end_hook.set(None);
+        #[cfg(feature = "finalization")]
+        state.finalize_phase_hook.set(None);
+        state.drop_phase_hook.set(None);
+
+        #[cfg(feature = "heap-introspection")]
+        state.diagnostics.replace(None);
+
+        #[cfg(feature = "std")]
+        {
+            state.thread_exited.set(false);
+            state.leak_report_callback.set(None);
+            state.last_collection_end.set(None);
+        }
+
+        #[cfg(all(feature = "auto-collect", feature = "std"))]
+        {
+            state.traced_object_cost.set(0.0);
+            state.collection_interrupted.set(false);
+        }
     });
 }
 
+type CollectionEndHook = fn(CollectionTrigger, &CollectionReport);
+
 pub(crate) struct State {
     collecting: Cell<bool>,
 
     #[cfg(feature = "finalization")]
     finalizing: Cell<bool>,
+    #[cfg(feature = "finalization")]
+    put_back: Cell<usize>, // Finalized objects left into POSSIBLE_CYCLES by the last collection and not yet checked again
 
     dropping: Cell<bool>,
     allocated_bytes: Cell<usize>,
+    external_bytes: Cell<usize>, // Memory owned by Ccs, reported through report_external_allocation
+    allocated_objects: Cell<usize>,
+    allocations_since_last_collection: Cell<usize>,
+    live_bytes_after_collection: Cell<usize>, // The allocated bytes at the end of the last collection
     executions_counter: Cell<usize>,
+    allocator: Cell<Option<&'static dyn CcAllocator>>,
+    allocator_allocations: Cell<usize>, // Number of live allocations made using allocator
+    current_report: Cell<CollectionReport>, // The report of the running collection
+    report_history: RefCell<ReportHistory>,
+
+    running_hook: Cell<bool>,
+    collection_requested: Cell<bool>, // Whether request_collection has been called while a collection couldn't be started
+    #[cfg(feature = "auto-collect")]
+    no_collect_depth: Cell<usize>, // The number of live NoCollectGuards
+    #[cfg(feature = "auto-collect")]
+    deferred_collection: Cell<Option<CollectionTrigger>>, // A collection which became due while suppressed
+    collection_start_hook: Cell<Option<fn(CollectionTrigger)>>,
+    collection_end_hook: Cell<Option<CollectionEndHook>>,
+    #[cfg(feature = "finalization")]
+    finalize_phase_hook: Cell<Option<fn(CollectionTrigger)>>,
+    drop_phase_hook: Cell<Option<fn(CollectionTrigger)>>,
+
+    #[cfg(feature = "heap-introspection")]
+    // Some only during collect_cycles_with_diagnostics. ManuallyDrop avoids making State needs_drop, otherwise
+    // the state would be destroyed at thread exit and Ccs couldn't be dropped from other thread locals' destructors
+    diagnostics: ManuallyDrop<RefCell<Option<CollectionDiagnostics>>>,
+
+    #[cfg(feature = "std")]
+    thread_exited: Cell<bool>,
+    #[cfg(feature = "std")]
+    leak_report_callback: Cell<Option<fn(LeakReport)>>,
+    #[cfg(feature = "std")]
+    last_collection_end: Cell<Option<Instant>>,
+    #[cfg(all(feature = "auto-collect", feature = "std"))]
+    traced_object_cost: Cell<f64>, // Average nanoseconds spent per traced object, 0 if no object has been traced yet
+    #[cfg(all(feature = "auto-collect", feature = "std"))]
+    collection_interrupted: Cell<bool>, // Whether the last collection has been interrupted to respect the maximum pause
 
     _phantom: PhantomData<Rc<()>>, // Make State !Send and !Sync
 }
@@ -66,10 +167,46 @@ const fn new() -> Self {
 
             #[cfg(feature = "finalization")]
             finalizing: Cell::new(false),
+            #[cfg(feature = "finalization")]
+            put_back: Cell::new(0),
 
             dropping: Cell::new(false),
             allocated_bytes: Cell::new(0),
+            external_bytes: Cell::new(0),
+            allocated_objects: Cell::new(0),
+            allocations_since_last_collection: Cell::new(0),
+            live_bytes_after_collection: Cell::new(0),
             executions_counter: Cell::new(0),
+            allocator: Cell::new(None),
+            allocator_allocations: Cell::new(0),
+            current_report: Cell::new(CollectionReport::new()),
+            report_history: RefCell::new(ReportHistory::new()),
+
+            running_hook: Cell::new(false),
+            collection_requested: Cell::new(false),
+            #[cfg(feature = "auto-collect")]
+            no_collect_depth: Cell::new(0),
+            #[cfg(feature = "auto-collect")]
+            deferred_collection: Cell::new(None),
+            collection_start_hook: Cell::new(None),
+            collection_end_hook: Cell::new(None),
+            #[cfg(feature = "finalization")]
+            finalize_phase_hook: Cell::new(None),
+            drop_phase_hook: Cell::new(None),
+
+            #[cfg(feature = "heap-introspection")]
+            diagnostics: ManuallyDrop::new(RefCell::new(None)),
+
+            #[cfg(feature = "std")]
+            thread_exited: Cell::new(false),
+            #[cfg(feature = "std")]
+            leak_report_callback: Cell::new(None),
+            #[cfg(feature = "std")]
+            last_collection_end: Cell::new(None),
+            #[cfg(all(feature = "auto-collect", feature = "std"))]
+            traced_object_cost: Cell::new(0.0),
+            #[cfg(all(feature = "auto-collect", feature = "std"))]
+            collection_interrupted: Cell::new(false),
 
             _phantom: PhantomData,
         }
@@ -80,14 +217,62 @@ pub(crate) fn allocated_bytes(&self) -> usize {
         self.allocated_bytes.get()
     }
 
+    #[inline]
+    pub(crate) fn external_bytes(&self) -> usize {
+        self.external_bytes.get()
+    }
+
+    /// Returns the allocated bytes plus the external bytes.
+    #[inline]
+    pub(crate) fn memory_pressure(&self) -> usize {
+        self.allocated_bytes.get().saturating_add(self.external_bytes.get())
+    }
+
+    #[inline]
+    #[allow(dead_code)] // Currently used only when std is enabled, but always keep it
+    pub(crate) fn allocated_objects(&self) -> usize {
+        self.allocated_objects.get()
+    }
+
     #[inline]
     pub(crate) fn record_allocation(&self, layout: Layout) {
         self.allocated_bytes.set(self.allocated_bytes.get() + layout.size());
+        self.allocated_objects.set(self.allocated_objects.get() + 1);
+        self.allocations_since_last_collection.set(self.allocations_since_last_collection.get() + 1);
+
+        #[cfg(feature = "global-stats")]
+        crate::global_stats::record_allocated_bytes(self.allocated_bytes.get());
+    }
+
+    #[cfg(feature = "auto-collect")]
+    #[inline]
+    pub(crate) fn allocations_since_last_collection(&self) -> usize {
+        self.allocations_since_last_collection.get()
+    }
+
+    #[inline]
+    pub(crate) fn reset_allocations_since_last_collection(&self) {
+        self.allocations_since_last_collection.set(0);
+    }
+
+    #[inline]
+    pub(crate) fn live_bytes_after_collection(&self) -> usize {
+        self.live_bytes_after_collection.get()
+    }
+
+    /// Records the allocated bytes (plus the external bytes) at the end of a collection, which are the bytes of the objects found alive.
+    #[inline]
+    pub(crate) fn record_live_bytes(&self) {
+        self.live_bytes_after_collection.set(self.memory_pressure());
     }
 
     #[inline]
     pub(crate) fn record_deallocation(&self, layout: Layout) {
         self.allocated_bytes.set(self.allocated_bytes.get() - layout.size());
+        self.allocated_objects.set(self.allocated_objects.get() - 1);
+
+        #[cfg(feature = "global-stats")]
+        crate::global_stats::record_allocated_bytes(self.allocated_bytes.get());
     }
 
     #[inline]
@@ -98,6 +283,198 @@ pub(crate) fn executions_count(&self) -> usize {
     #[inline]
     pub(super) fn increment_executions_count(&self) {
         self.executions_counter.set(self.executions_counter.get() + 1);
+
+        #[cfg(feature = "global-stats")]
+        crate::global_stats::record_executions_count(self.executions_counter.get());
+    }
+
+    #[inline]
+    pub(crate) fn allocator(&self) -> Option<&'static dyn CcAllocator> {
+        self.allocator.get()
+    }
+
+    #[inline]
+    pub(crate) fn set_allocator(&self, allocator: Option<&'static dyn CcAllocator>) {
+        self.allocator.set(allocator);
+    }
+
+    #[inline]
+    pub(crate) fn allocator_allocations(&self) -> usize {
+        self.allocator_allocations.get()
+    }
+
+    #[inline]
+    pub(crate) fn record_allocator_allocation(&self) {
+        self.allocator_allocations.set(self.allocator_allocations.get() + 1);
+    }
+
+    #[inline]
+    pub(crate) fn record_allocator_deallocation(&self) {
+        self.allocator_allocations.set(self.allocator_allocations.get() - 1);
+    }
+
+    #[inline]
+    pub(crate) fn update_report(&self, f: impl FnOnce(&mut CollectionReport)) {
+        let mut report = self.current_report.get();
+        f(&mut report);
+        self.current_report.set(report);
+    }
+
+    /// Takes the report of the running collection, resetting it.
+    #[inline]
+    pub(crate) fn take_report(&self) -> CollectionReport {
+        self.current_report.replace(CollectionReport::new())
+    }
+
+    #[inline]
+    pub(crate) fn push_report(&self, report: CollectionReport) {
+        // Reports are pushed only at the end of a collection, so the history is never already borrowed
+        if let Ok(mut history) = self.report_history.try_borrow_mut() {
+            history.push(report);
+        }
+    }
+
+    #[cfg(feature = "auto-collect")]
+    #[inline]
+    pub(crate) fn last_report(&self) -> Option<CollectionReport> {
+        self.report_history.try_borrow().ok().and_then(|history| history.last())
+    }
+
+    #[cfg(all(feature = "std", feature = "auto-collect"))]
+    #[inline]
+    pub(crate) fn last_collection_end(&self) -> Option<Instant> {
+        self.last_collection_end.get()
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    pub(crate) fn set_last_collection_end(&self, end: Instant) {
+        self.last_collection_end.set(Some(end));
+    }
+
+    /// Updates the average time spent per traced object using the statistics of a collection.
+    #[cfg(all(feature = "auto-collect", feature = "std"))]
+    #[inline]
+    pub(crate) fn record_traced_object_cost(&self, duration: Duration, traced: usize) {
+        if traced == 0 {
+            return;
+        }
+
+        let sample = duration.as_nanos() as f64 / traced as f64;
+        let cost = self.traced_object_cost.get();
+        if cost == 0.0 {
+            self.traced_object_cost.set(sample);
+        } else {
+            // Give more weight to the history, to smooth out outliers
+            self.traced_object_cost.set((cost * 3.0 + sample) / 4.0);
+        }
+    }
+
+    /// Returns the number of objects which can be traced within `pause`, or [`None`] if it's not known yet.
+    /// At least 1 is always returned, so that collections can always make progress.
+    #[cfg(all(feature = "auto-collect", feature = "std"))]
+    #[inline]
+    pub(crate) fn traced_objects_within(&self, pause: Duration) -> Option<usize> {
+        let cost = self.traced_object_cost.get();
+        (cost > 0.0).then(|| usize::max(1, (pause.as_nanos() as f64 / cost) as usize))
+    }
+
+    #[cfg(all(feature = "auto-collect", feature = "std"))]
+    #[inline]
+    pub(crate) fn is_collection_interrupted(&self) -> bool {
+        self.collection_interrupted.get()
+    }
+
+    #[cfg(all(feature = "auto-collect", feature = "std"))]
+    #[inline]
+    pub(crate) fn set_collection_interrupted(&self, interrupted: bool) {
+        self.collection_interrupted.set(interrupted);
+    }
+
+    #[inline]
+    pub(crate) fn is_running_hook(&self) -> bool {
+        self.running_hook.get()
+    }
+
+    #[inline]
+    pub(crate) fn set_running_hook(&self, value: bool) {
+        self.running_hook.set(value);
+    }
+
+    #[inline]
+    pub(crate) fn is_collection_requested(&self) -> bool {
+        self.collection_requested.get()
+    }
+
+    #[inline]
+    pub(crate) fn set_collection_requested(&self, value: bool) {
+        self.collection_requested.set(value);
+    }
+
+    #[cfg(feature = "auto-collect")]
+    #[inline]
+    pub(crate) fn are_collections_suppressed(&self) -> bool {
+        self.no_collect_depth.get() != 0
+    }
+
+    #[cfg(feature = "auto-collect")]
+    #[inline]
+    pub(crate) fn suppress_collections(&self) {
+        self.no_collect_depth.set(self.no_collect_depth.get() + 1);
+    }
+
+    /// Returns `true` if the last suppression has been removed.
+    #[cfg(feature = "auto-collect")]
+    #[inline]
+    pub(crate) fn unsuppress_collections(&self) -> bool {
+        let depth = self.no_collect_depth.get() - 1;
+        self.no_collect_depth.set(depth);
+        depth == 0
+    }
+
+    #[cfg(feature = "auto-collect")]
+    #[inline]
+    pub(crate) fn is_collection_deferred(&self) -> bool {
+        self.deferred_collection.get().is_some()
+    }
+
+    #[cfg(feature = "auto-collect")]
+    #[inline]
+    pub(crate) fn defer_collection(&self, trigger: CollectionTrigger) {
+        self.deferred_collection.set(Some(trigger));
+    }
+
+    #[cfg(feature = "auto-collect")]
+    #[inline]
+    pub(crate) fn take_deferred_collection(&self) -> Option<CollectionTrigger> {
+        self.deferred_collection.take()
+    }
+
+    #[inline]
+    pub(crate) fn collection_start_hook(&self) -> Option<fn(CollectionTrigger)> {
+        self.collection_start_hook.get()
+    }
+
+    #[inline]
+    pub(crate) fn collection_end_hook(&self) -> Option<CollectionEndHook> {
+        self.collection_end_hook.get()
+    }
+
+    #[cfg(feature = "finalization")]
+    #[inline]
+    pub(crate) fn finalize_phase_hook(&self) -> Option<fn(CollectionTrigger)> {
+        self.finalize_phase_hook.get()
+    }
+
+    #[inline]
+    pub(crate) fn drop_phase_hook(&self) -> Option<fn(CollectionTrigger)> {
+        self.drop_phase_hook.get()
+    }
+
+    #[cfg(feature = "heap-introspection")]
+    #[inline]
+    pub(crate) fn diagnostics(&self) -> &RefCell<Option<CollectionDiagnostics>> {
+        &self.diagnostics
     }
 
     #[inline]
@@ -122,6 +499,18 @@ pub(crate) fn set_finalizing(&self, value: bool) {
         self.finalizing.set(value);
     }
 
+    #[cfg(feature = "finalization")]
+    #[inline]
+    pub(crate) fn take_put_back(&self) -> usize {
+        self.put_back.replace(0)
+    }
+
+    #[cfg(feature = "finalization")]
+    #[inline]
+    pub(crate) fn set_put_back(&self, put_back: usize) {
+        self.put_back.set(put_back);
+    }
+
     #[inline]
     pub(crate) fn is_dropping(&self) -> bool {
         self.dropping.get()
@@ -132,6 +521,24 @@ pub(crate) fn set_dropping(&self, value: bool) {
         self.dropping.set(value);
     }
 
+    #[cfg(feature = "std")]
+    #[inline]
+    pub(crate) fn is_thread_exited(&self) -> bool {
+        self.thread_exited.get()
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    pub(crate) fn set_thread_exited(&self, value: bool) {
+        self.thread_exited.set(value);
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    pub(crate) fn leak_report_callback(&self) -> Option<fn(LeakReport)> {
+        self.leak_report_callback.get()
+    }
+
     #[inline]
     #[allow(dead_code)] // Currently used only inside #[cfg(debug_assertions)], but always keep it
     pub(crate) fn is_tracing(&self) -> bool {
@@ -175,12 +582,146 @@ pub fn allocated_bytes() -> Result<usize, StateAccessError> {
     try_state(|state| Ok(state.allocated_bytes()))?
 }
 
+/// Returns the number of allocated bytes measured at the end of the last collection, i.e. the bytes which
+/// have been found alive by the last collection. Returns 0 if no collection has been executed yet.
+///
+/// The returned bytes include the external bytes (see [`report_external_allocation`]).
+#[inline]
+pub fn live_bytes_after_last_collection() -> Result<usize, StateAccessError> {
+    try_state(|state| Ok(state.live_bytes_after_collection()))?
+}
+
+/// Reports that the objects managed by the garbage collector have allocated (if `delta` is positive) or deallocated
+/// (if `delta` is negative) `delta` bytes of external memory, i.e. memory not allocated by [`Cc`][`crate::Cc`] itself.
+///
+/// [`allocated_bytes`] only counts the memory allocated by [`Cc`][`crate::Cc`], so a `Cc<Vec<u8>>` holding a big buffer
+/// looks like a small allocation. The external bytes are added to the allocated bytes when deciding whether to start
+/// a collection automatically and when enforcing the heap limit (see the [`config` module documentation][`mod@crate::config`]).
+///
+/// The external bytes never go below 0. Reporting external memory doesn't start a collection by itself.
+///
+/// # Example
+/// ```rust
+///# use rust_cc::*;
+///# use rust_cc::state::*;
+/// struct Buffer {
+///     data: Vec<u8>,
+/// }
+///# unsafe impl Trace for Buffer {
+///#     fn trace(&self, _: &mut Context<'_>) {}
+///# }
+///# impl Finalize for Buffer {}
+///
+/// impl Buffer {
+///     fn new(len: usize) -> Cc<Buffer> {
+///         report_external_allocation(len as isize).unwrap();
+///         Cc::new(Buffer { data: vec![0; len] })
+///     }
+/// }
+///
+/// impl Drop for Buffer {
+///     fn drop(&mut self) {
+///         let _ = report_external_allocation(-(self.data.len() as isize));
+///     }
+/// }
+///
+/// let buffer = Buffer::new(1024);
+/// assert_eq!(1024, external_bytes().unwrap());
+/// drop(buffer);
+/// assert_eq!(0, external_bytes().unwrap());
+/// ```
+#[inline]
+pub fn report_external_allocation(delta: isize) -> Result<(), StateAccessError> {
+    try_state(|state| {
+        let external = state.external_bytes.get().saturating_add_signed(delta);
+        state.external_bytes.set(external);
+    })
+}
+
+/// Returns the number of bytes of external memory reported using [`report_external_allocation`].
+#[inline]
+pub fn external_bytes() -> Result<usize, StateAccessError> {
+    try_state(|state| Ok(state.external_bytes()))?
+}
+
+/// Returns `true` if a collection requested using [`request_collection`][`fn@crate::request_collection`]
+/// is pending, i.e. it hasn't been executed yet.
+#[inline]
+pub fn is_collection_requested() -> Result<bool, StateAccessError> {
+    try_state(|state| Ok(state.is_collection_requested()))?
+}
+
 /// Returns the total number of executed collections.
 #[inline]
 pub fn executions_count() -> Result<usize, StateAccessError> {
     try_state(|state| Ok(state.executions_count()))?
 }
 
+/// Returns the reports of the last collections executed by the current thread, from the oldest to the newest.
+///
+/// Only the last 16 reports are kept.
+///
+/// # Example
+/// ```rust
+///# use rust_cc::*;
+///# use rust_cc::state::last_collections;
+/// let report = collect_cycles().unwrap();
+/// assert_eq!(Some(&report), last_collections().unwrap().last());
+/// ```
+#[inline]
+pub fn last_collections() -> Result<Vec<CollectionReport>, StateAccessError> {
+    try_state(|state| {
+        state.report_history.try_borrow().map(|history| history.to_vec()).map_err(|_| StateAccessError::AccessError)
+    })?
+}
+
+/// Sets the function called when a collection starts, replacing the previous one.
+///
+/// Collections cannot be started while a hook is running, and hooks are not called from inside other hooks.
+/// Passing [`None`] removes the hook.
+///
+/// # Example
+/// ```rust
+///# use rust_cc::*;
+///# use rust_cc::state::*;
+/// on_collection_start(Some(|trigger: CollectionTrigger| {
+///     println!("Collection started: {trigger:?}");
+/// })).unwrap();
+///
+/// collect_cycles();
+/// ```
+#[inline]
+pub fn on_collection_start(hook: Option<fn(CollectionTrigger)>) -> Result<(), StateAccessError> {
+    try_state(|state| state.collection_start_hook.set(hook))
+}
+
+/// Sets the function called with the [`CollectionReport`] when a collection ends, replacing the previous one.
+///
+/// See [`on_collection_start`] for more details.
+#[inline]
+pub fn on_collection_end(hook: Option<fn(CollectionTrigger, &CollectionReport)>) -> Result<(), StateAccessError> {
+    try_state(|state| state.collection_end_hook.set(hook))
+}
+
+/// Sets the function called before garbage objects are finalized, replacing the previous one.
+///
+/// This hook may be called more than once per collection (see [`CollectionReport::iterations`]).
+///
+/// See [`on_collection_start`] for more details.
+#[cfg(feature = "finalization")]
+#[inline]
+pub fn on_finalize_phase(hook: Option<fn(CollectionTrigger)>) -> Result<(), StateAccessError> {
+    try_state(|state| state.finalize_phase_hook.set(hook))
+}
+
+/// Sets the function called before garbage objects are dropped and deallocated, replacing the previous one.
+///
+/// See [`on_collection_start`] for more details.
+#[inline]
+pub fn on_drop_phase(hook: Option<fn(CollectionTrigger)>) -> Result<(), StateAccessError> {
+    try_state(|state| state.drop_phase_hook.set(hook))
+}
+
 /// Returns `true` if the garbage collector is in a tracing phase, `false` otherwise.
 ///
 /// See [`Trace`][`trait@crate::Trace`] for more details.
@@ -189,14 +730,95 @@ pub fn is_tracing() -> Result<bool, StateAccessError> {
     try_state(|state| Ok(state.is_tracing()))?
 }
 
+/// Sets the function called with a [`LeakReport`] when the current thread exits, replacing the previous one.
+///
+/// When a thread exits, a last collection is executed to free the remaining garbage cycles. After that, the provided
+/// `callback` is called with the number of objects (and bytes) which are still allocated, i.e. which have been leaked.
+///
+/// Any [`Cc`][`crate::Cc`] dropped after the last collection (for example, from the destructor of another thread local)
+/// is collected by another collection, executed after the destructor which dropped it (or immediately, when that's not
+/// possible). Since such collections trace every object reachable from the dropped [`Cc`][`crate::Cc`]s, dropping
+/// many [`Cc`][`crate::Cc`]s of a big object graph from different thread locals' destructors may be expensive.
+///
+/// Passing [`None`] disables the report.
+///
+/// # Example
+/// ```rust
+///# use rust_cc::state::*;
+/// std::thread::spawn(|| {
+///     set_leak_report_callback(Some(|report: LeakReport| {
+///         assert_eq!(0, report.objects());
+///     })).unwrap();
+///
+///     // ...
+/// }).join().unwrap();
+/// ```
+#[cfg(feature = "std")]
+#[inline]
+pub fn set_leak_report_callback(callback: Option<fn(LeakReport)>) -> Result<(), StateAccessError> {
+    try_state(|state| state.leak_report_callback.set(callback))
+}
+
+/// The objects still allocated when a thread exited.
+///
+/// See [`set_leak_report_callback`] for more details.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeakReport {
+    objects: usize,
+    bytes: usize,
+    #[cfg(feature = "allocation-sites")]
+    allocation_sites: Vec<crate::allocation_sites::SiteStats>,
+}
+
+#[cfg(feature = "std")]
+impl LeakReport {
+    #[inline]
+    pub(crate) fn new(objects: usize, bytes: usize) -> Self {
+        Self {
+            objects,
+            bytes,
+            #[cfg(feature = "allocation-sites")]
+            allocation_sites: crate::allocation_sites::live_sites().unwrap_or_default(),
+        }
+    }
+
+    /// Returns the number of objects still allocated.
+    #[inline]
+    pub fn objects(&self) -> usize {
+        self.objects
+    }
+
+    /// Returns the number of bytes still allocated.
+    #[inline]
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Returns the objects still allocated, grouped by allocation site.
+    ///
+    /// See the [`allocation_sites` module documentation][`mod@crate::allocation_sites`] for more details.
+    #[cfg(feature = "allocation-sites")]
+    #[inline]
+    pub fn allocation_sites(&self) -> &[crate::allocation_sites::SiteStats] {
+        &self.allocation_sites
+    }
+}
+
 /// Utility macro used internally to implement drop guards that accesses the state
 macro_rules! replace_state_field {
+    (collecting, $value:expr, $state:ident) => {
+        $crate::state::replace_state_field!(__internal is_collecting, set_collecting, bool, $value, $state)
+    };
     (dropping, $value:expr, $state:ident) => {
         $crate::state::replace_state_field!(__internal is_dropping, set_dropping, bool, $value, $state)
     };
     (finalizing, $value:expr, $state:ident) => {
         $crate::state::replace_state_field!(__internal is_finalizing, set_finalizing, bool, $value, $state)
     };
+    (running_hook, $value:expr, $state:ident) => {
+        $crate::state::replace_state_field!(__internal is_running_hook, set_running_hook, bool, $value, $state)
+    };
     (__internal $is_name:ident, $set_name:ident, $field_type:ty, $value:expr, $state:ident) => {
         {
             let old_value: $field_type = $crate::state::State::$is_name($state);
@@ -230,6 +852,10 @@ mod tests {
 
     #[test]
     fn test_replace_state_field() {
+        // SAFETY: tests are run serially when critical-section is enabled
+        #[cfg(feature = "critical-section")]
+        unsafe { crate::init_global_state() };
+
         state(|state| {
             // Test state.dropping = true
             state.set_dropping(true);
@@ -1,20 +1,6 @@
 use super::*;
 use crate::*;
 
-struct Circular {
-    cc: Cell<Option<Cc<Droppable<Circular>>>>,
-}
-
-unsafe impl Trace for Circular {
-    fn trace(&self, ctx: &mut Context<'_>) {
-        if let Some(cc) = unsafe { &*self.cc.as_ptr() } {
-            cc.trace(ctx);
-        }
-    }
-}
-
-impl Finalize for Circular {}
-
 #[test]
 fn test_simple() {
     reset_state();
@@ -0,0 +1,174 @@
+use std::cell::RefCell;
+
+use super::*;
+use crate::*;
+use crate::state::last_collections;
+
+#[test]
+fn garbage_cycle() {
+    reset_state();
+
+    let (droppable1, checker1) = Droppable::new(Circular {
+        cc: Cell::new(None),
+    });
+    let cc1 = Cc::new(droppable1);
+
+    let (droppable2, checker2) = Droppable::new(Circular {
+        cc: Cell::new(None),
+    });
+    let cc2 = Cc::new(droppable2);
+
+    cc1.cc.set(Some(cc2.clone()));
+    cc2.cc.set(Some(cc1.clone()));
+    drop(cc1);
+    drop(cc2);
+
+    let report = collect_cycles().unwrap();
+    checker1.assert_dropped();
+    checker2.assert_dropped();
+    assert_eq!(0, report.traced_roots());
+    assert_eq!(0, report.roots());
+    assert_eq!(2, report.dropped());
+    assert_eq!(2, report.freed());
+    assert!(report.freed_bytes() >= 2 * size_of::<Droppable<Circular>>());
+
+    #[cfg(feature = "finalization")]
+    {
+        // The objects are finalized in the first iteration and deallocated in the second one
+        assert_eq!(2, report.iterations());
+        assert_eq!(4, report.candidates());
+        assert_eq!(4, report.traced_counting());
+        assert_eq!(2, report.finalized());
+        assert_eq!(0, report.resurrected());
+    }
+
+    #[cfg(not(feature = "finalization"))]
+    {
+        assert_eq!(1, report.iterations());
+        assert_eq!(2, report.candidates());
+        assert_eq!(2, report.traced_counting());
+    }
+}
+
+#[test]
+fn alive_cycle() {
+    reset_state();
+
+    let (droppable1, checker1) = Droppable::new(Circular {
+        cc: Cell::new(None),
+    });
+    let cc1 = Cc::new(droppable1);
+
+    let (droppable2, checker2) = Droppable::new(Circular {
+        cc: Cell::new(None),
+    });
+    let cc2 = Cc::new(droppable2);
+
+    cc1.cc.set(Some(cc2.clone()));
+    cc2.cc.set(Some(cc1.clone()));
+    drop(cc2);
+
+    let report = collect_cycles().unwrap();
+    checker1.assert_not_dropped();
+    checker2.assert_not_dropped();
+    assert_eq!(1, report.candidates());
+    assert_eq!(2, report.traced_counting());
+    assert_eq!(2, report.traced_roots());
+    assert_eq!(1, report.roots());
+    assert_eq!(0, report.dropped());
+    assert_eq!(0, report.freed());
+    assert_eq!(0, report.freed_bytes());
+    assert_eq!(1, report.iterations());
+
+    // Break the cycle to avoid leaking it
+    cc1.cc.set(None);
+}
+
+#[test]
+fn acyclic_objects_are_freed() {
+    reset_state();
+
+    struct Holder {
+        cyclic: RefCell<Option<Cc<Holder>>>,
+        _acyclic: Cc<u64>,
+    }
+
+    unsafe impl Trace for Holder {
+        fn trace(&self, ctx: &mut Context<'_>) {
+            self.cyclic.trace(ctx);
+            self._acyclic.trace(ctx);
+        }
+    }
+
+    impl Finalize for Holder {}
+
+    let holder = Cc::new(Holder {
+        cyclic: RefCell::new(None),
+        _acyclic: Cc::new(5),
+    });
+    *holder.cyclic.borrow_mut() = Some(holder.clone());
+    drop(holder);
+
+    let report = collect_cycles().unwrap();
+    assert_eq!(2, report.dropped()); // The acyclic Cc is also traced, so it's garbage too
+    assert_eq!(2, report.freed());
+    assert_empty();
+}
+
+#[cfg(feature = "finalization")]
+#[test]
+fn resurrection() {
+    reset_state();
+
+    thread_local! {
+        static RESURRECTED: RefCell<Option<Cc<Resurrecting>>> = const { RefCell::new(None) };
+    }
+
+    struct Resurrecting {
+        cyclic: RefCell<Option<Cc<Resurrecting>>>,
+    }
+
+    unsafe impl Trace for Resurrecting {
+        fn trace(&self, ctx: &mut Context<'_>) {
+            self.cyclic.trace(ctx);
+        }
+    }
+
+    impl Finalize for Resurrecting {
+        fn finalize(&self) {
+            RESURRECTED.with(|res| *res.borrow_mut() = self.cyclic.borrow().clone());
+        }
+    }
+
+    let cc = Cc::new(Resurrecting {
+        cyclic: RefCell::new(None),
+    });
+    *cc.cyclic.borrow_mut() = Some(cc.clone());
+    drop(cc);
+
+    let report = collect_cycles().unwrap();
+    assert_eq!(1, report.finalized());
+    assert_eq!(1, report.resurrected());
+    assert_eq!(0, report.freed());
+
+    // Break the cycle and collect
+    let cc = RESURRECTED.with(|res| res.borrow_mut().take()).unwrap();
+    *cc.cyclic.borrow_mut() = None;
+}
+
+#[test]
+fn history() {
+    reset_state();
+
+    let mut reports = Vec::new();
+    for _ in 0..20 {
+        let (cc, checker) = new_circular();
+        drop(cc);
+        reports.push(collect_cycles().unwrap());
+        checker.assert_dropped();
+    }
+
+    let history = last_collections().unwrap();
+    assert_eq!(16, history.len());
+    assert_eq!(&reports[4..], &history[..]);
+}
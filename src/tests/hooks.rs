@@ -0,0 +1,119 @@
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+
+use super::*;
+use crate::*;
+use crate::state::{CollectionReport, CollectionTrigger, on_collection_end, on_collection_start, on_drop_phase};
+
+thread_local! {
+    static EVENTS: RefCell<Vec<(&'static str, CollectionTrigger)>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record(event: &'static str, trigger: CollectionTrigger) {
+    EVENTS.with(|events| events.borrow_mut().push((event, trigger)));
+}
+
+fn take_events() -> Vec<(&'static str, CollectionTrigger)> {
+    EVENTS.with(|events| events.take())
+}
+
+fn reset_hooks() {
+    on_collection_start(None).unwrap();
+    on_collection_end(None).unwrap();
+    #[cfg(feature = "finalization")]
+    state::on_finalize_phase(None).unwrap();
+    on_drop_phase(None).unwrap();
+}
+
+#[test]
+fn hooks_order() {
+    reset_state();
+
+    on_collection_start(Some(|trigger| record("start", trigger))).unwrap();
+    on_collection_end(Some(|trigger, report: &CollectionReport| {
+        assert_eq!(trigger, report.trigger());
+        record("end", trigger);
+    })).unwrap();
+    #[cfg(feature = "finalization")]
+    state::on_finalize_phase(Some(|trigger| record("finalize", trigger))).unwrap();
+    on_drop_phase(Some(|trigger| record("drop", trigger))).unwrap();
+
+    let (cc, checker) = new_circular();
+    drop(cc);
+    let report = collect_cycles().unwrap();
+    reset_hooks();
+
+    checker.assert_dropped();
+    assert_eq!(CollectionTrigger::Manual, report.trigger());
+
+    let events: Vec<&str> = take_events().into_iter().map(|(event, trigger)| {
+        assert_eq!(CollectionTrigger::Manual, trigger);
+        event
+    }).collect();
+
+    #[cfg(feature = "finalization")]
+    assert_eq!(vec!["start", "finalize", "finalize", "drop", "end"], events);
+    #[cfg(not(feature = "finalization"))]
+    assert_eq!(vec!["start", "drop", "end"], events);
+}
+
+#[test]
+fn no_collection_inside_hooks() {
+    reset_state();
+
+    on_collection_start(Some(|trigger| {
+        assert!(collect_cycles().is_none());
+        record("start", trigger);
+    })).unwrap();
+    on_collection_end(Some(|trigger, _: &CollectionReport| {
+        assert!(collect_cycles().is_none());
+        record("end", trigger);
+    })).unwrap();
+
+    let (cc, checker) = new_circular();
+    drop(cc);
+    assert!(collect_cycles().is_some());
+    reset_hooks();
+
+    checker.assert_dropped();
+    assert_eq!(2, take_events().len());
+}
+
+#[test]
+fn panicking_hook() {
+    reset_state();
+
+    on_collection_start(Some(|_| panic!("Panicking hook"))).unwrap();
+
+    let (cc, checker) = new_circular();
+    drop(cc);
+    assert!(panic::catch_unwind(AssertUnwindSafe(collect_cycles)).is_err());
+    checker.assert_not_dropped();
+
+    // The state must be restored after a panic
+    assert_state_not_collecting();
+    on_collection_start(Some(|trigger| record("start", trigger))).unwrap();
+    assert!(collect_cycles().is_some());
+    reset_hooks();
+
+    checker.assert_dropped();
+    assert_eq!(vec![("start", CollectionTrigger::Manual)], take_events());
+}
+
+#[cfg(feature = "auto-collect")]
+#[test]
+fn automatic_trigger() {
+    use crate::config::config;
+
+    reset_state();
+
+    config(|config| config.set_auto_collect(true)).unwrap();
+
+    let _big = Cc::new([0u8; 256]); // Exceed the default threshold of 100 bytes
+
+    on_collection_start(Some(|trigger| record("start", trigger))).unwrap();
+    let _cc = Cc::new(5); // Start a collection
+    reset_hooks();
+
+    assert_eq!(vec![("start", CollectionTrigger::BytesThreshold)], take_events());
+}
@@ -0,0 +1,28 @@
+use crate::Cc;
+use crate::introspection::{deregister, explain_retention, IntrospectionError, register, write_escaped};
+
+fn escaped(s: &str) -> String {
+    let mut res = String::new();
+    write_escaped(&mut res, s).unwrap();
+    res
+}
+
+#[test]
+fn test_write_escaped() {
+    assert_eq!("rust_cc::Cc<alloc::string::String>", escaped("rust_cc::Cc<alloc::string::String>"));
+    assert_eq!(r#"\"quoted\" \\path\\"#, escaped(r#""quoted" \path\"#));
+    assert_eq!(r"a\nb\rc\td", escaped("a\nb\rc\td"));
+    assert_eq!(r"\u0000\u001f", escaped("\u{0}\u{1f}"));
+    assert_eq!("\u{7f}é", escaped("\u{7f}é"));
+}
+
+#[test]
+fn test_explain_retention_untracked() {
+    let cc = Cc::new(5u32);
+    assert!(explain_retention(&cc).unwrap().is_garbage());
+
+    // Simulate an object allocated by another thread
+    deregister(cc.inner_ptr().cast());
+    assert!(matches!(explain_retention(&cc), Err(IntrospectionError::NotTracked)));
+    register(cc.inner_ptr().cast());
+}
@@ -142,6 +142,7 @@ fn test_for_each_clearing_panic(mut list: impl ListMethodsExt) {
         }
     }
 
+    #[allow(clippy::never_loop)]
     let res = catch_unwind(AssertUnwindSafe(|| list.into_iter().for_each(|ptr| {
         // Manually set mark for the first CcBox, the others should be handled by List::drop
         unsafe { ptr.as_ref().counter_marker().mark(Mark::NonMarked) };
@@ -1,7 +1,9 @@
 #![cfg(test)]
 
-use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::cell::Cell;
+use std::mem::ManuallyDrop;
 use std::ops::{Deref, DerefMut};
 
 use crate::trace::Trace;
@@ -14,6 +16,9 @@
 mod list;
 mod panicking;
 mod counter_marker;
+mod thread_exit;
+mod collection_report;
+mod hooks;
 
 #[cfg(feature = "weak-ptr")]
 mod weak;
@@ -21,9 +26,12 @@
 #[cfg(feature = "cleaners")]
 mod cleaners;
 
+#[cfg(feature = "heap-introspection")]
+mod introspection;
+
 pub(crate) fn reset_state() {
     POSSIBLE_CYCLES.with(|pc| {
-        pc.replace(CountedList::new());
+        drop(ManuallyDrop::into_inner(pc.replace(ManuallyDrop::new(CountedList::new()))));
     });
     state::reset_state();
 
@@ -37,14 +45,14 @@ pub(crate) fn reset_state() {
 pub(crate) struct Droppable<T: Trace> {
     inner: T,
     #[allow(unused)]
-    finalize: Rc<Cell<bool>>,
-    drop: Rc<Cell<bool>>,
+    finalize: Arc<AtomicBool>,
+    drop: Arc<AtomicBool>,
 }
 
 impl<T: Trace> Droppable<T> {
     pub(crate) fn new(t: T) -> (Droppable<T>, DropChecker) {
-        let finalize = Rc::new(Cell::new(false));
-        let drop = Rc::new(Cell::new(false));
+        let finalize = Arc::new(AtomicBool::new(false));
+        let drop = Arc::new(AtomicBool::new(false));
         (
             Droppable {
                 inner: t,
@@ -86,7 +94,7 @@ fn finalize(&self) {
         #[cfg(feature = "finalization")]
         {
             assert_finalizing();
-            self.finalize.set(true);
+            self.finalize.store(true, Ordering::Relaxed);
         }
     }
 }
@@ -95,36 +103,60 @@ impl<T: Trace> Drop for Droppable<T> {
     fn drop(&mut self) {
         assert_dropping();
         // Set arc value to true
-        self.drop.set(true);
+        self.drop.store(true, Ordering::Relaxed);
     }
 }
 
 pub(crate) struct DropChecker {
     #[allow(unused)]
-    finalize: Rc<Cell<bool>>,
-    drop: Rc<Cell<bool>>,
+    finalize: Arc<AtomicBool>,
+    drop: Arc<AtomicBool>,
 }
 
 impl DropChecker {
     pub(crate) fn assert_finalized(&self) {
         #[cfg(feature = "finalization")]
-        assert!(self.finalize.get(), "Expected finalized!");
+        assert!(self.finalize.load(Ordering::Relaxed), "Expected finalized!");
     }
 
     pub(crate) fn assert_not_finalized(&self) {
         #[cfg(feature = "finalization")]
-        assert!(!self.finalize.get(), "Expected not finalized!");
+        assert!(!self.finalize.load(Ordering::Relaxed), "Expected not finalized!");
     }
 
     pub(crate) fn assert_dropped(&self) {
-        assert!(self.drop.get(), "Expected dropped!");
+        assert!(self.drop.load(Ordering::Relaxed), "Expected dropped!");
     }
 
     pub(crate) fn assert_not_dropped(&self) {
-        assert!(!self.drop.get(), "Expected not dropped!");
+        assert!(!self.drop.load(Ordering::Relaxed), "Expected not dropped!");
+    }
+}
+
+pub(crate) struct Circular {
+    pub(crate) cc: Cell<Option<Cc<Droppable<Circular>>>>,
+}
+
+unsafe impl Trace for Circular {
+    fn trace(&self, ctx: &mut Context<'_>) {
+        if let Some(cc) = unsafe { &*self.cc.as_ptr() } {
+            cc.trace(ctx);
+        }
     }
 }
 
+impl Finalize for Circular {}
+
+/// Creates a [`Circular`] pointing to itself.
+pub(crate) fn new_circular() -> (Cc<Droppable<Circular>>, DropChecker) {
+    let (droppable, checker) = Droppable::new(Circular {
+        cc: Cell::new(None),
+    });
+    let cc = Cc::new(droppable);
+    cc.cc.set(Some(cc.clone()));
+    (cc, checker)
+}
+
 pub(crate) fn assert_empty() {
     let list = POSSIBLE_CYCLES.with(|pc| pc.borrow().first());
     assert!(list.is_none());
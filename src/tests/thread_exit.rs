@@ -0,0 +1,109 @@
+use std::cell::RefCell;
+use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use super::*;
+use crate::*;
+use crate::state::{CollectionTrigger, LeakReport, on_collection_start, set_leak_report_callback};
+
+#[test]
+fn collect_at_thread_exit() {
+    let checker = thread::spawn(|| {
+        let (cc, checker) = new_circular();
+        drop(cc);
+        checker.assert_not_dropped();
+        checker
+    }).join().unwrap();
+
+    checker.assert_finalized();
+    checker.assert_dropped();
+}
+
+#[test]
+fn drop_from_thread_local_destructor() {
+    thread_local! {
+        static HOLDER: RefCell<Option<Cc<Droppable<Circular>>>> = const { RefCell::new(None) };
+    }
+
+    let checker = thread::spawn(|| {
+        // Access HOLDER before allocating any Cc, so that its destructor is registered first
+        HOLDER.with(|_| {});
+
+        let (cc, checker) = new_circular();
+        HOLDER.with(|holder| *holder.borrow_mut() = Some(cc));
+        checker
+    }).join().unwrap();
+
+    checker.assert_dropped();
+}
+
+#[test]
+fn drops_from_thread_local_destructor_are_batched() {
+    thread_local! {
+        static HOLDER: RefCell<Vec<Cc<Droppable<Circular>>>> = const { RefCell::new(Vec::new()) };
+    }
+
+    static THREAD_EXIT_COLLECTIONS: AtomicUsize = AtomicUsize::new(0);
+
+    fn on_start(trigger: CollectionTrigger) {
+        if trigger == CollectionTrigger::ThreadExit {
+            THREAD_EXIT_COLLECTIONS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let checkers = thread::spawn(|| {
+        // Access HOLDER before allocating any Cc, so that its destructor is registered first
+        HOLDER.with(|_| {});
+        on_collection_start(Some(on_start)).unwrap();
+
+        let (ccs, checkers): (Vec<_>, Vec<_>) = (0..100).map(|_| new_circular()).unzip();
+        HOLDER.with(|holder| holder.borrow_mut().extend(ccs));
+        checkers
+    }).join().unwrap();
+
+    checkers.iter().for_each(DropChecker::assert_dropped);
+
+    // One collection executed at thread exit and a single one for all the Ccs dropped by HOLDER's destructor
+    assert_eq!(2, THREAD_EXIT_COLLECTIONS.load(Ordering::Relaxed));
+}
+
+#[test]
+fn leak_report() {
+    static OBJECTS: AtomicUsize = AtomicUsize::new(usize::MAX);
+    static BYTES: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+    fn callback(report: LeakReport) {
+        OBJECTS.store(report.objects(), Ordering::Relaxed);
+        BYTES.store(report.bytes(), Ordering::Relaxed);
+    }
+
+    thread::spawn(|| {
+        set_leak_report_callback(Some(callback)).unwrap();
+
+        drop(new_circular()); // Collected at thread exit
+
+        mem::forget(Cc::new(42u64)); // Leaked
+    }).join().unwrap();
+
+    assert_eq!(1, OBJECTS.load(Ordering::Relaxed));
+    assert_ne!(0, BYTES.load(Ordering::Relaxed));
+}
+
+#[test]
+fn no_leak_report() {
+    static OBJECTS: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+    fn callback(report: LeakReport) {
+        OBJECTS.store(report.objects(), Ordering::Relaxed);
+    }
+
+    thread::spawn(|| {
+        set_leak_report_callback(Some(callback)).unwrap();
+
+        let _cc = Cc::new(42u64);
+        drop(new_circular());
+    }).join().unwrap();
+
+    assert_eq!(0, OBJECTS.load(Ordering::Relaxed));
+}
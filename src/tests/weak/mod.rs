@@ -150,7 +150,7 @@ impl Finalize for Cyclic {}
 fn panicking_new_cyclic1() {
     reset_state();
 
-    let _cc = Cc::new_cyclic(|_| {
+    let _cc = Cc::<Weakable<()>>::new_cyclic(|_| {
         panic!("Expected panic during panicking_new_cyclic1!");
     });
 }
@@ -160,7 +160,7 @@ fn panicking_new_cyclic1() {
 fn panicking_new_cyclic2() {
     reset_state();
 
-    let _cc = Cc::new_cyclic(|weak| {
+    let _cc = Cc::<Weakable<()>>::new_cyclic(|weak| {
         let _weak = weak.clone();
         panic!("Expected panic during panicking_new_cyclic2!");
     });
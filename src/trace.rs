@@ -22,6 +22,8 @@
 };
 
 use crate::List;
+#[cfg(feature = "graph-walk")]
+use {core::ptr::NonNull, crate::CcBox};
 
 /// Trait to finalize objects before freeing them.
 ///
@@ -133,6 +135,8 @@ pub unsafe trait Trace: Finalize {
 /// The tracing context provided to every invocation of [`Trace::trace`].
 pub struct Context<'a> {
     inner: ContextInner<'a>,
+    traced: usize, // Number of traced objects
+    roots: usize, // Number of roots found (only while root tracing)
     _phantom: PhantomData<*mut ()>, // Make Context !Send and !Sync
 }
 
@@ -145,6 +149,10 @@ pub(crate) enum ContextInner<'a> {
         root_list: &'a mut List,
         non_root_list: &'a mut List,
     },
+    #[cfg(feature = "graph-walk")]
+    Introspection {
+        edges: &'a mut Vec<NonNull<CcBox<()>>>,
+    },
 }
 
 impl<'b> Context<'b> {
@@ -153,10 +161,32 @@ impl<'b> Context<'b> {
     pub(crate) const fn new(ctxi: ContextInner) -> Context {
         Context {
             inner: ctxi,
+            traced: 0,
+            roots: 0,
             _phantom: PhantomData,
         }
     }
 
+    #[inline(always)]
+    pub(crate) fn record_traced(&mut self) {
+        self.traced += 1;
+    }
+
+    #[inline(always)]
+    pub(crate) fn record_root(&mut self) {
+        self.roots += 1;
+    }
+
+    #[inline]
+    pub(crate) fn traced(&self) -> usize {
+        self.traced
+    }
+
+    #[inline]
+    pub(crate) fn roots(&self) -> usize {
+        self.roots
+    }
+
     #[inline]
     pub(crate) fn inner<'a>(&'a mut self) -> &'a mut ContextInner<'b>
         where
@@ -0,0 +1,162 @@
+//! Per-type statistics about the objects allocated by the current thread.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::any::TypeId;
+use core::cell::RefCell;
+use core::ptr::NonNull;
+
+use crate::cc::CcBox;
+use crate::state::StateAccessError;
+use crate::utils;
+
+utils::rust_cc_thread_local! {
+    static TYPE_STATS: RefCell<BTreeMap<TypeId, TypeStats>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+#[inline]
+fn with_type_stats(ptr: NonNull<CcBox<()>>, f: impl FnOnce(&mut TypeStats)) {
+    // The statistics may have already been destroyed if the thread is exiting
+    let _ = TYPE_STATS.try_with(|type_stats| {
+        if let Ok(mut type_stats) = type_stats.try_borrow_mut() {
+            let stats = type_stats.entry(CcBox::type_id(ptr)).or_insert_with(|| TypeStats::new(CcBox::type_name(ptr)));
+            f(stats);
+        }
+    });
+}
+
+/// Must be called after the CcBox has been initialized, since its vtable is used to get the type.
+#[inline]
+pub(crate) fn record_allocation(ptr: NonNull<CcBox<()>>, size: usize) {
+    with_type_stats(ptr, |stats| {
+        stats.live_objects += 1;
+        stats.live_bytes += size;
+    });
+}
+
+#[inline]
+pub(crate) fn record_deallocation(ptr: NonNull<CcBox<()>>, size: usize) {
+    with_type_stats(ptr, |stats| {
+        stats.live_objects = stats.live_objects.saturating_sub(1);
+        stats.live_bytes = stats.live_bytes.saturating_sub(size);
+        stats.freed += 1;
+    });
+}
+
+#[inline]
+pub(crate) fn record_buffered(ptr: NonNull<CcBox<()>>) {
+    with_type_stats(ptr, |stats| stats.buffered += 1);
+}
+
+/// Records that `ptr` is going to be deallocated by the collector.
+#[inline]
+pub(crate) fn record_collected(ptr: NonNull<CcBox<()>>) {
+    with_type_stats(ptr, |stats| stats.collected += 1);
+}
+
+/// Returns the statistics of every type which has been allocated inside a [`Cc`][`crate::Cc`] by the current thread.
+///
+/// The types with more live bytes come first.
+///
+/// The statistics can be used to find which types are buffered the most, i.e. which types may benefit from calls to
+/// [`mark_alive`][`crate::Cc::mark_alive`] or from being restructured to avoid creating cycles.
+pub fn type_stats() -> Result<Vec<TypeStats>, StateAccessError> {
+    #[cfg_attr(not(feature = "allocation-sites"), allow(unused_mut))]
+    let mut type_stats = TYPE_STATS.try_with(|type_stats| {
+        type_stats.try_borrow().map(|type_stats| type_stats.clone()).map_err(|_| StateAccessError::AccessError)
+    }).unwrap_or(Err(StateAccessError::AccessError))?;
+
+    #[cfg(feature = "allocation-sites")]
+    {
+        let mut objects: BTreeMap<TypeId, Vec<_>> = BTreeMap::new();
+        for (ptr, location) in crate::allocation_sites::live_objects()? {
+            let size = unsafe { ptr.as_ref() }.layout().size();
+            objects.entry(CcBox::type_id(ptr)).or_default().push((location, size));
+        }
+        for (type_id, objects) in objects {
+            if let Some(stats) = type_stats.get_mut(&type_id) {
+                stats.allocation_sites = crate::allocation_sites::SiteStats::group(objects);
+            }
+        }
+    }
+
+    let mut type_stats: Vec<TypeStats> = type_stats.into_values().collect();
+    type_stats.sort_by(|a, b| b.live_bytes.cmp(&a.live_bytes).then(a.type_name.cmp(b.type_name)));
+    Ok(type_stats)
+}
+
+/// The statistics of a single type, returned by [`type_stats`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeStats {
+    type_name: &'static str,
+    live_objects: usize,
+    live_bytes: usize,
+    buffered: usize,
+    freed: usize,
+    collected: usize,
+    #[cfg(feature = "allocation-sites")]
+    allocation_sites: Vec<crate::allocation_sites::SiteStats>, // Only filled by type_stats()
+}
+
+impl TypeStats {
+    #[inline]
+    const fn new(type_name: &'static str) -> Self {
+        Self {
+            type_name,
+            live_objects: 0,
+            live_bytes: 0,
+            buffered: 0,
+            freed: 0,
+            collected: 0,
+            #[cfg(feature = "allocation-sites")]
+            allocation_sites: Vec::new(),
+        }
+    }
+
+    /// Returns the name of the type, as returned by [`type_name`][`core::any::type_name`].
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns the number of objects of this type which are still allocated.
+    #[inline]
+    pub fn live_objects(&self) -> usize {
+        self.live_objects
+    }
+
+    /// Returns the number of bytes allocated by the objects of this type which are still allocated.
+    #[inline]
+    pub fn live_bytes(&self) -> usize {
+        self.live_bytes
+    }
+
+    /// Returns how many times an object of this type has been put into the buffer of possible cycles.
+    ///
+    /// See [`buffered_objects_count`][`fn@crate::state::buffered_objects_count`] for more details.
+    #[inline]
+    pub fn buffered(&self) -> usize {
+        self.buffered
+    }
+
+    /// Returns the number of objects of this type which have been freed by the collector.
+    #[inline]
+    pub fn freed_by_collection(&self) -> usize {
+        self.collected
+    }
+
+    /// Returns the number of objects of this type which have been freed since their reference count reached zero.
+    #[inline]
+    pub fn freed_by_refcount(&self) -> usize {
+        self.freed - self.collected
+    }
+
+    /// Returns the live objects of this type, grouped by allocation site.
+    ///
+    /// See the [`allocation_sites` module documentation][`mod@crate::allocation_sites`] for more details.
+    #[cfg(feature = "allocation-sites")]
+    #[inline]
+    pub fn allocation_sites(&self) -> &[crate::allocation_sites::SiteStats] {
+        &self.allocation_sites
+    }
+}
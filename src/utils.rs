@@ -2,15 +2,37 @@
 use core::ptr::NonNull;
 
 use crate::{CcBox, Trace};
+use crate::allocator::CcAllocator;
 use crate::state::State;
+#[cfg(any(feature = "weak-ptr", feature = "cleaners"))]
+use crate::state::{state, try_state};
 
 #[inline]
 pub(crate) unsafe fn cc_alloc<T: Trace + 'static>(layout: Layout, state: &State) -> NonNull<CcBox<T>> {
+    #[cfg(feature = "std")]
+    crate::register_thread_exit_guard();
+
+    let ptr = thread_alloc(layout, state);
     state.record_allocation(layout);
-    match NonNull::new(alloc(layout) as *mut CcBox<T>) {
+    #[cfg(feature = "heap-introspection")]
+    crate::introspection::register(ptr.cast());
+    ptr.cast()
+}
+
+/// Allocates a `CcBox` using the provided allocator instead of the thread's one.
+#[inline]
+pub(crate) unsafe fn cc_alloc_in<T, A: CcAllocator + ?Sized>(allocator: &A, layout: Layout, state: &State) -> NonNull<T> {
+    #[cfg(feature = "std")]
+    crate::register_thread_exit_guard();
+
+    let ptr = match allocator.allocate(layout) {
         Some(ptr) => ptr,
         None => handle_alloc_error(layout),
-    }
+    };
+    state.record_allocation(layout);
+    #[cfg(feature = "heap-introspection")]
+    crate::introspection::register(ptr.cast());
+    ptr.cast()
 }
 
 #[inline]
@@ -20,24 +42,62 @@ pub(crate) unsafe fn cc_dealloc<T: ?Sized + Trace + 'static>(
     state: &State
 ) {
     state.record_deallocation(layout);
-    dealloc(ptr.cast().as_ptr(), layout);
+    #[cfg(feature = "heap-introspection")]
+    crate::introspection::deregister(ptr.cast());
+    #[cfg(feature = "type-stats")]
+    crate::type_stats::record_deallocation(ptr.cast(), layout.size());
+    #[cfg(feature = "allocation-sites")]
+    crate::allocation_sites::remove(ptr.cast());
+
+    if state.is_collecting() {
+        state.update_report(|report| {
+            report.freed += 1;
+            report.freed_bytes += layout.size();
+        });
+    }
+
+    // The CcBox may have been allocated using Cc::new_in, so let it choose how to deallocate itself
+    let deallocator = CcBox::deallocator(ptr.cast());
+    deallocator(ptr.cast(), layout, state);
 }
 
-#[cfg(any(feature = "weak-ptr", feature = "cleaners"))]
+/// Allocates using the thread's allocator, or the global allocator if none is installed.
 #[inline]
-pub(crate) unsafe fn alloc_other<T>() -> NonNull<T> {
-    let layout = Layout::new::<T>();
-    match NonNull::new(alloc(layout) as *mut T) {
-        Some(ptr) => ptr,
+pub(crate) unsafe fn thread_alloc(layout: Layout, state: &State) -> NonNull<u8> {
+    let ptr = match state.allocator() {
+        Some(allocator) => allocator.allocate(layout),
+        None => NonNull::new(alloc(layout)),
+    };
+    match ptr {
+        Some(ptr) => {
+            state.record_allocator_allocation();
+            ptr
+        },
         None => handle_alloc_error(layout),
     }
 }
 
+/// Deallocates memory allocated by [`thread_alloc`].
+#[inline]
+pub(crate) unsafe fn thread_dealloc(ptr: NonNull<u8>, layout: Layout, state: &State) {
+    match state.allocator() {
+        Some(allocator) => allocator.deallocate(ptr, layout),
+        None => dealloc(ptr.as_ptr(), layout),
+    }
+    state.record_allocator_deallocation();
+}
+
+#[cfg(any(feature = "weak-ptr", feature = "cleaners"))]
+#[inline]
+pub(crate) unsafe fn alloc_other<T>() -> NonNull<T> {
+    state(|state| thread_alloc(Layout::new::<T>(), state).cast())
+}
+
 #[cfg(any(feature = "weak-ptr", feature = "cleaners"))]
 #[inline]
 pub(crate) unsafe fn dealloc_other<T>(ptr: NonNull<T>) {
-    let layout = Layout::new::<T>();
-    dealloc(ptr.cast().as_ptr(), layout);
+    // Use try_state to avoid panicking inside Drop implementations. The state is always accessible anyway
+    let _ = try_state(|state| thread_dealloc(ptr.cast(), Layout::new::<T>(), state));
 }
 
 #[inline(always)]
@@ -71,8 +131,13 @@ macro_rules! rust_cc_thread_local {
         #[allow(clippy::declare_interior_mutable_const)]
         const INIT: $t = $init;
 
+        #[cfg(not(feature = "critical-section"))]
         #[thread_local]
         $(#[$attr])* $vis static $name: $crate::utils::NoStdLocalKey<$t> = $crate::utils::NoStdLocalKey::new(INIT);
+
+        // When using critical sections, there's a single global value instead of one for every thread
+        #[cfg(feature = "critical-section")]
+        $(#[$attr])* $vis static $name: $crate::utils::GlobalKey<$t> = $crate::utils::GlobalKey::new(INIT);
     );
 }
 
@@ -82,6 +147,10 @@ macro_rules! rust_cc_thread_local {
     no_std_thread_locals::*,
 };
 
+#[cfg(all(not(feature = "std"), feature = "critical-section"))]
+pub(crate) use global_state::GlobalKey;
+
+
 #[cfg(not(feature = "std"))]
 #[allow(dead_code)]
 mod no_std_thread_locals {
@@ -119,7 +188,92 @@ pub(crate) fn try_with<F, R>(&self, f: F) -> Result<R, AccessError>
     }
 }
 
-#[cfg(all(test, not(feature = "std")))]
+/// Enables the global collector state used when the `critical-section` feature is enabled and `std` is not.
+///
+/// Until this function is called, any use of the collector panics (or fails, for functions returning a [`Result`]).
+///
+/// When the `std` feature is enabled the collector state is kept inside thread locals and this function does nothing.
+///
+/// # Safety
+///
+/// The collector state is shared by the whole program. The caller must guarantee that the collector is never used
+/// concurrently by more than one execution context. In particular, [`Cc`]s must not be used from interrupt handlers
+/// which can preempt other code using [`Cc`]s, nor from more than one core.
+///
+/// [`Cc`]: crate::Cc
+#[cfg(feature = "critical-section")]
+#[inline]
+pub unsafe fn init_global_state() {
+    #[cfg(not(feature = "std"))]
+    global_state::init();
+}
+
+#[cfg(all(not(feature = "std"), feature = "critical-section"))]
+#[allow(dead_code)]
+mod global_state {
+    // Global replacement for LocalKey used by the critical-section feature. Sharing a single collector state between
+    // every execution context is sound only if they never use it concurrently (CcBoxes are modified outside of the
+    // critical sections, for example by Cc::clone), so access is denied until the user opts in calling init_global_state
+
+    use core::cell::Cell;
+    use critical_section::Mutex;
+
+    use super::AccessError;
+
+    static INITIALIZED: Mutex<Cell<bool>> = Mutex::new(Cell::new(false));
+
+    #[inline]
+    pub(super) fn init() {
+        critical_section::with(|cs| INITIALIZED.borrow(cs).set(true));
+    }
+
+    #[cfg(test)]
+    pub(super) fn deinit() {
+        critical_section::with(|cs| INITIALIZED.borrow(cs).set(false));
+    }
+
+    pub(crate) struct GlobalKey<T: 'static> {
+        value: T,
+    }
+
+    // SAFETY: value is accessed only after init_global_state has been called,
+    //         whose caller guarantees that the collector is never used concurrently
+    unsafe impl<T: 'static> Sync for GlobalKey<T> {}
+
+    impl<T: 'static> GlobalKey<T> {
+        #[inline(always)]
+        pub(crate) const fn new(value: T) -> Self {
+            GlobalKey { value }
+        }
+
+        #[inline]
+        pub(crate) fn with<F, R>(&self, f: F) -> R
+            where
+            F: FnOnce(&T) -> R,
+        {
+            match self.try_with(f) {
+                Ok(res) => res,
+                Err(_) => panic!("the global collector state is not initialized, call rust_cc::init_global_state first"),
+            }
+        }
+
+        #[inline]
+        pub(crate) fn try_with<F, R>(&self, f: F) -> Result<R, AccessError>
+            where
+            F: FnOnce(&T) -> R,
+        {
+            critical_section::with(|cs| {
+                if INITIALIZED.borrow(cs).get() {
+                    Ok(f(&self.value))
+                } else {
+                    Err(AccessError)
+                }
+            })
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "std"), not(feature = "critical-section")))]
 mod no_std_tests {
     use core::cell::Cell;
     use super::no_std_thread_locals::NoStdLocalKey;
@@ -179,3 +333,44 @@ fn test_try_with_nested() {
         assert_eq!(4, i);
     }
 }
+
+#[cfg(all(test, not(feature = "std"), feature = "critical-section"))]
+mod critical_section_tests {
+    extern crate std;
+
+    use core::cell::Cell;
+    use super::global_state::{deinit, GlobalKey};
+    use super::init_global_state;
+
+    rust_cc_thread_local! {
+        static VAL: Cell<i32> = Cell::new(3);
+    }
+
+    #[test]
+    fn check_type() {
+        // Make sure we're using the right macro
+        fn a(_: &GlobalKey<Cell<i32>>) {}
+        a(&VAL);
+    }
+
+    // VAL and the initialization are shared between every test thread, so use them only in a single test
+    #[test]
+    fn test_global_value() {
+        deinit();
+        assert!(VAL.try_with(|i| i.get()).is_err());
+        assert!(std::panic::catch_unwind(|| VAL.with(|i| i.get())).is_err());
+
+        unsafe { init_global_state() };
+
+        let i = VAL.with(|i| {
+            i.set(VAL.try_with(|ii| ii.get()).unwrap() + 1);
+            i.get()
+        });
+        assert_eq!(4, i);
+
+        let i = std::thread::spawn(|| {
+            VAL.with(|i| i.get())
+        }).join().unwrap();
+        assert_eq!(4, i);
+    }
+}
@@ -23,7 +23,7 @@
 use crate::utils::{alloc_other, cc_dealloc, dealloc_other};
 use crate::weak::weak_metadata::WeakMetadata;
 
-mod weak_metadata;
+pub(crate) mod weak_metadata;
 
 /// A [`Cc`] which can be [`downgrade`][`method@Cc::downgrade`]d to a [`Weak`] pointer.
 pub type WeakableCc<T> = Cc<Weakable<T>>;
@@ -288,6 +288,8 @@ pub fn new_cyclic<F>(f: F) -> Cc<Weakable<T>>
         mem::forget(cc);
 
         let metadata: NonNull<WeakMetadata> = unsafe { invalid_cc.as_ref() }.get_elem().init_get_metadata();
+        #[cfg(feature = "heap-introspection")]
+        crate::introspection::set_weak_metadata(invalid_cc.cast(), metadata);
 
         // Set weak counter to 1
         // This is done after creating the Cc to make sure that if Cc::new panics the metadata allocation isn't leaked
@@ -369,6 +371,8 @@ pub fn downgrade(&self) -> Weak<T> {
         }
 
         let metadata = self.init_get_metadata();
+        #[cfg(feature = "heap-introspection")]
+        crate::introspection::set_weak_metadata(self.inner_ptr().cast(), metadata);
 
         if unsafe { metadata.as_ref() }.increment_counter().is_err() {
             panic!("Too many references has been created to a single Weak");
@@ -0,0 +1,131 @@
+#![cfg(feature = "allocation-sites")]
+
+use std::panic::Location;
+
+use rust_cc::{Cc, collect_cycles};
+use rust_cc::allocation_sites::{live_sites, pprof_profile, SiteStats};
+
+mod common;
+
+use common::{new_node, Node};
+
+fn site_stats(sites: &[SiteStats], location: &Location<'static>) -> Option<SiteStats> {
+    sites.iter().copied().find(|stats| stats.location() == location)
+}
+
+#[test]
+fn allocation_site() {
+    let (cc, location) = (Cc::new(5u32), Location::caller());
+    let site = cc.allocation_site().unwrap();
+    assert_eq!(file!(), site.file());
+    assert_eq!(location.line(), site.line());
+
+    // The location of the caller is propagated through #[track_caller] functions
+    let (node, location) = (new_node(), Location::caller());
+    assert_eq!(location.line(), node.allocation_site().unwrap().line());
+}
+
+#[test]
+fn group_by_site() {
+    let mut nodes = Vec::new();
+    let mut location = None;
+    for _ in 0..3 {
+        nodes.push(new_node());
+        location = nodes.last().unwrap().allocation_site();
+    }
+    let other = new_node();
+
+    let sites = live_sites().unwrap();
+    let stats = site_stats(&sites, location.unwrap()).unwrap();
+    assert_eq!(3, stats.objects());
+    assert!(stats.bytes() >= 3 * size_of::<Node>());
+    assert_eq!(1, site_stats(&sites, other.allocation_site().unwrap()).unwrap().objects());
+
+    // Freed objects are removed
+    *nodes[0].next.borrow_mut() = Some(nodes[0].clone());
+    drop(nodes);
+    collect_cycles();
+    assert!(site_stats(&live_sites().unwrap(), location.unwrap()).is_none());
+}
+
+#[test]
+fn pprof() {
+    let _cc = Cc::new(5u32);
+
+    let profile = pprof_profile().unwrap();
+    let contains = |s: &str| profile.windows(s.len()).any(|window| window == s.as_bytes());
+    assert!(contains("inuse_objects"));
+    assert!(contains("inuse_space"));
+    assert!(contains(file!()));
+
+    // The first field is a sample_type (field 1, length-delimited)
+    assert_eq!(0x0a, profile[0]);
+}
+
+#[cfg(feature = "type-stats")]
+#[test]
+fn type_stats() {
+    use rust_cc::{Context, Finalize, Trace};
+    use rust_cc::state::type_stats;
+
+    struct Sited;
+
+    unsafe impl Trace for Sited {
+        fn trace(&self, _: &mut Context<'_>) {}
+    }
+
+    impl Finalize for Sited {}
+
+    let a = Cc::new(Sited);
+    let b = Cc::new(Sited);
+
+    let stats = type_stats().unwrap().into_iter().find(|stats| stats.type_name().ends_with("Sited")).unwrap();
+    assert_eq!(2, stats.allocation_sites().len());
+    assert!(stats.allocation_sites().iter().all(|site| site.objects() == 1));
+    assert!(site_stats(stats.allocation_sites(), a.allocation_site().unwrap()).is_some());
+    assert!(site_stats(stats.allocation_sites(), b.allocation_site().unwrap()).is_some());
+}
+
+#[cfg(feature = "heap-introspection")]
+#[test]
+fn heap_dump() {
+    use rust_cc::introspection::heap_dump;
+
+    let node = new_node();
+    let site = node.allocation_site().unwrap();
+
+    let dump = heap_dump().unwrap();
+    let object = dump.objects().iter().find(|object| object.allocation_site() == Some(site)).unwrap();
+    assert!(object.type_name().ends_with("Node"));
+    assert_eq!(1, site_stats(&dump.allocation_sites(), site).unwrap().objects());
+
+    assert!(dump.to_json().contains(&format!("\"allocation_site\":\"{site}\"")));
+    assert!(dump.to_dot().contains(&format!("\\nat {site}")));
+}
+
+#[cfg(all(feature = "std", not(feature = "critical-section")))]
+#[test]
+fn leak_report() {
+    use std::sync::Mutex;
+    use rust_cc::state::{LeakReport, set_leak_report_callback};
+
+    static SITES: Mutex<Vec<SiteStats>> = Mutex::new(Vec::new());
+
+    fn callback(report: LeakReport) {
+        *SITES.lock().unwrap() = report.allocation_sites().to_vec();
+    }
+
+    let site = std::thread::spawn(|| {
+        set_leak_report_callback(Some(callback)).unwrap();
+
+        let leaked = Cc::new(42u64);
+        let site = leaked.allocation_site().unwrap();
+        std::mem::forget(leaked);
+        site
+    }).join().unwrap();
+
+    let sites = SITES.lock().unwrap();
+    assert_eq!(1, sites.len());
+    assert_eq!(site, sites[0].location());
+    assert_eq!(1, sites[0].objects());
+}
@@ -0,0 +1,181 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::ptr::NonNull;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rust_cc::{Cc, collect_cycles};
+use rust_cc::allocator::CcAllocator;
+
+mod common;
+
+use common::Node;
+
+struct Counting {
+    allocations: AtomicUsize,
+    deallocations: AtomicUsize,
+}
+
+impl Counting {
+    const fn new() -> Counting {
+        Counting {
+            allocations: AtomicUsize::new(0),
+            deallocations: AtomicUsize::new(0),
+        }
+    }
+
+    fn allocations(&self) -> usize {
+        self.allocations.load(Ordering::Relaxed)
+    }
+
+    fn deallocations(&self) -> usize {
+        self.deallocations.load(Ordering::Relaxed)
+    }
+}
+
+unsafe impl CcAllocator for Counting {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        NonNull::new(unsafe { System.alloc(layout) })
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.deallocations.fetch_add(1, Ordering::Relaxed);
+        System.dealloc(ptr.as_ptr(), layout);
+    }
+}
+
+// A per-heap allocator handle
+#[derive(Clone)]
+struct Heap(Rc<Counting>);
+
+unsafe impl CcAllocator for Heap {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        self.0.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.0.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+fn new_in() {
+    let heap = Heap(Rc::new(Counting::new()));
+
+    let cc = Cc::new_in(5u64, heap.clone());
+    assert_eq!(5, *cc);
+    assert_eq!(1, heap.0.allocations());
+    assert_eq!(2, Rc::strong_count(&heap.0)); // One is stored inside the allocation
+
+    drop(cc);
+    assert_eq!(1, heap.0.deallocations());
+    assert_eq!(1, Rc::strong_count(&heap.0));
+}
+
+#[test]
+fn new_in_cycle() {
+    let heap = Heap(Rc::new(Counting::new()));
+
+    let cc = Cc::new_in(Node::default(), heap.clone());
+    *cc.next.borrow_mut() = Some(cc.clone());
+    drop(cc);
+
+    assert_eq!(0, heap.0.deallocations());
+    collect_cycles();
+    assert_eq!(1, heap.0.deallocations());
+    assert_eq!(1, Rc::strong_count(&heap.0));
+}
+
+#[test]
+fn new_in_into_inner() {
+    let heap = Heap(Rc::new(Counting::new()));
+
+    let cc = Cc::new_in(String::from("inner"), heap.clone());
+    assert_eq!("inner", cc.into_inner());
+    assert_eq!(1, heap.0.deallocations());
+    assert_eq!(1, Rc::strong_count(&heap.0));
+}
+
+// When critical-section is enabled the state is shared between every test,
+// so installing an allocator would affect the other tests
+#[cfg(feature = "std")]
+mod thread_allocator {
+    use std::thread;
+
+    use rust_cc::allocator::{set_thread_allocator, SetAllocatorError, thread_allocator};
+
+    use super::*;
+    use super::common::new_node;
+
+    #[test]
+    fn thread_allocator_is_used() {
+        static COUNTING: Counting = Counting::new();
+
+        thread::spawn(|| {
+            set_thread_allocator(Some(&COUNTING)).unwrap();
+            assert!(thread_allocator().unwrap().is_some());
+
+            let cc = new_node();
+            *cc.next.borrow_mut() = Some(cc.clone());
+            let _other = Cc::new(5u32);
+            drop(cc);
+
+            assert_eq!(2, COUNTING.allocations());
+            collect_cycles();
+            assert_eq!(1, COUNTING.deallocations());
+        }).join().unwrap();
+
+        assert_eq!(2, COUNTING.deallocations());
+    }
+
+    #[test]
+    fn cannot_replace_in_use_allocator() {
+        static COUNTING: Counting = Counting::new();
+
+        thread::spawn(|| {
+            set_thread_allocator(Some(&COUNTING)).unwrap();
+
+            let cc = Cc::new(5u32);
+            assert!(matches!(set_thread_allocator(None), Err(SetAllocatorError::InUse)));
+
+            // Objects allocated with Cc::new_in are deallocated with their own allocator
+            let heap = Heap(Rc::new(Counting::new()));
+            let _in_heap = Cc::new_in(5u32, heap);
+
+            drop(cc);
+            set_thread_allocator(None).unwrap();
+            assert!(thread_allocator().unwrap().is_none());
+        }).join().unwrap();
+
+        assert_eq!(1, COUNTING.allocations());
+        assert_eq!(1, COUNTING.deallocations());
+    }
+
+    #[cfg(feature = "weak-ptr")]
+    #[test]
+    fn weak_metadata() {
+        use rust_cc::weak::WeakableCc;
+
+        static COUNTING: Counting = Counting::new();
+
+        thread::spawn(|| {
+            set_thread_allocator(Some(&COUNTING)).unwrap();
+
+            let cc: WeakableCc<u32> = Cc::new_weakable(5);
+            let weak = cc.downgrade();
+            assert_eq!(2, COUNTING.allocations()); // The CcBox and the weak metadata
+
+            drop(cc);
+            assert_eq!(1, COUNTING.deallocations());
+            drop(weak);
+            assert_eq!(2, COUNTING.deallocations());
+        }).join().unwrap();
+    }
+}
+
+#[cfg(feature = "nightly")]
+#[test]
+fn new_in_allocator_api() {
+    let cc = Cc::new_in(5u32, std::alloc::System);
+    assert_eq!(5, *cc);
+}
@@ -7,6 +7,8 @@
 use rust_cc::config::config;
 use rust_cc::state::executions_count;
 
+mod common;
+
 struct Traceable {
     inner: RefCell<Option<Cc<Traceable>>>,
     _big: Big,
@@ -158,3 +160,41 @@ fn new<T: Default + 'static>() -> Cc<Cyclic<T>> {
     assert_eq!(executions_counter + 1, executions_count().unwrap(), "Didn't collected");
     collect_cycles(); // Make sure to don't leak test's memory
 }
+
+#[test]
+fn test_external_memory_auto_collect() {
+    use rust_cc::state::{CollectionTrigger, external_bytes, last_collections, report_external_allocation};
+
+    const EXTERNAL: isize = 1 << 30;
+
+    // Always remove the reported external memory, even with panics
+    struct DropGuard;
+    impl Drop for DropGuard {
+        fn drop(&mut self) {
+            report_external_allocation(-EXTERNAL).expect("Couldn't remove external memory");
+        }
+    }
+
+    collect_cycles(); // Reset the threshold
+
+    let executions_counter = executions_count().unwrap();
+    let external = external_bytes().unwrap();
+    report_external_allocation(EXTERNAL).unwrap();
+    let _drop_guard = DropGuard;
+    assert_eq!(external + EXTERNAL as usize, external_bytes().unwrap());
+    assert_eq!(executions_counter, executions_count().unwrap(), "Collected but shouldn't have collected.");
+
+    let _cc = Cc::new(0u8); // Collection should be triggered by the external memory
+    assert_eq!(executions_counter + 1, executions_count().unwrap(), "Didn't collected");
+    assert_eq!(CollectionTrigger::BytesThreshold, last_collections().unwrap().last().unwrap().trigger());
+}
+
+#[test]
+fn test_external_memory_saturates() {
+    use rust_cc::state::{external_bytes, report_external_allocation};
+
+    let external = external_bytes().unwrap();
+    report_external_allocation(-(external as isize) - 100).unwrap();
+    assert_eq!(0, external_bytes().unwrap());
+    report_external_allocation(external as isize).unwrap();
+}
@@ -0,0 +1,221 @@
+#![cfg(feature = "auto-collect")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rust_cc::{Cc, collect_cycles};
+use rust_cc::config::{CollectionPolicy, config, Config, PolicyContext};
+use rust_cc::state::{CollectionTrigger, executions_count, last_collections};
+
+mod common;
+
+#[derive(Debug, Clone)]
+struct EveryNAllocations(usize);
+
+impl CollectionPolicy for EveryNAllocations {
+    fn should_collect(&mut self, ctx: &PolicyContext<'_>) -> bool {
+        ctx.allocations_since_last_collection() >= self.0
+    }
+}
+
+fn reset_policy() {
+    config(|config| {
+        config.take_policy();
+    }).unwrap();
+}
+
+#[test]
+fn allocation_count_policy() {
+    config(|config| config.set_policy(Box::new(EveryNAllocations(10)))).unwrap();
+    collect_cycles(); // Reset the allocations count
+
+    let executions = executions_count().unwrap();
+    let ccs: Vec<_> = (0..10).map(Cc::new).collect();
+    assert_eq!(executions, executions_count().unwrap());
+
+    let _cc = Cc::new(10);
+    assert_eq!(executions + 1, executions_count().unwrap());
+    assert_eq!(CollectionTrigger::Policy, last_collections().unwrap().last().unwrap().trigger());
+
+    reset_policy();
+    drop(ccs);
+}
+
+#[derive(Debug, Clone)]
+struct CountingPolicy;
+
+static ADJUSTED: AtomicUsize = AtomicUsize::new(0);
+
+impl CollectionPolicy for CountingPolicy {
+    fn should_collect(&mut self, _: &PolicyContext<'_>) -> bool {
+        false
+    }
+
+    fn adjust(&mut self, ctx: &PolicyContext<'_>) {
+        assert_eq!(0, ctx.allocations_since_last_collection());
+        assert_eq!(Some(CollectionTrigger::Manual), ctx.last_report().map(|report| report.trigger()));
+        ADJUSTED.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn adjust_after_every_collection() {
+    config(|config| config.set_policy(Box::new(CountingPolicy))).unwrap();
+
+    let adjusted = ADJUSTED.load(Ordering::Relaxed);
+    collect_cycles();
+    collect_cycles();
+    assert_eq!(adjusted + 2, ADJUSTED.load(Ordering::Relaxed));
+
+    reset_policy();
+}
+
+#[cfg(feature = "finalization")]
+#[test]
+fn finalizer_allocations_are_not_counted() {
+    use std::cell::{Cell, RefCell};
+    use rust_cc::{Context, Finalize, Trace};
+
+    thread_local! {
+        static ALLOCATIONS: Cell<Option<usize>> = const { Cell::new(None) };
+    }
+
+    #[derive(Debug, Clone)]
+    struct Recording;
+
+    impl CollectionPolicy for Recording {
+        fn should_collect(&mut self, ctx: &PolicyContext<'_>) -> bool {
+            ALLOCATIONS.with(|allocations| allocations.set(Some(ctx.allocations_since_last_collection())));
+            false
+        }
+    }
+
+    struct Allocating {
+        cyclic: RefCell<Option<Cc<Allocating>>>,
+    }
+
+    unsafe impl Trace for Allocating {
+        fn trace(&self, ctx: &mut Context<'_>) {
+            self.cyclic.trace(ctx);
+        }
+    }
+
+    impl Finalize for Allocating {
+        fn finalize(&self) {
+            for i in 0..5 {
+                let _ = Cc::new(i);
+            }
+        }
+    }
+
+    config(|config| config.set_policy(Box::new(Recording))).unwrap();
+
+    let cc = Cc::new(Allocating {
+        cyclic: RefCell::new(None),
+    });
+    *cc.cyclic.borrow_mut() = Some(cc.clone());
+    drop(cc);
+    collect_cycles();
+
+    // The policy is asked before the new object is counted
+    let _cc = Cc::new(0);
+    assert_eq!(Some(0), ALLOCATIONS.with(Cell::get));
+
+    reset_policy();
+}
+
+#[test]
+fn take_policy_restores_default() {
+    config(|config| config.set_policy(Box::new(CountingPolicy))).unwrap();
+    let policy = config(|config| config.take_policy()).unwrap();
+    assert!(policy.is_some());
+    assert!(config(|config| config.take_policy()).unwrap().is_none());
+}
+
+#[test]
+fn policy_is_cloned_with_config() {
+    config(|config| config.set_policy(Box::new(EveryNAllocations(5)))).unwrap();
+
+    let cloned = config(|config| config.clone()).unwrap();
+    assert!(format!("{cloned:?}").contains("EveryNAllocations(5)"));
+
+    reset_policy();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn time_since_last_collection() {
+    use std::time::Duration;
+
+    #[derive(Debug, Clone)]
+    struct Timed;
+
+    impl CollectionPolicy for Timed {
+        fn should_collect(&mut self, ctx: &PolicyContext<'_>) -> bool {
+            ctx.time_since_last_collection().is_some_and(|time| time >= Duration::from_millis(20))
+        }
+    }
+
+    config(|config| config.set_policy(Box::new(Timed))).unwrap();
+    collect_cycles();
+
+    let executions = executions_count().unwrap();
+    let _a = Cc::new(0);
+    assert_eq!(executions, executions_count().unwrap());
+
+    std::thread::sleep(Duration::from_millis(30));
+    let _b = Cc::new(0);
+    assert_eq!(executions + 1, executions_count().unwrap());
+
+    reset_policy();
+}
+
+#[test]
+fn proportional_growth() {
+    use rust_cc::config::GrowthStrategy;
+    use rust_cc::state::{allocated_bytes, live_bytes_after_last_collection};
+
+    config(|config| config.set_growth_strategy(GrowthStrategy::Proportional { percent: 100 })).unwrap();
+
+    let before = allocated_bytes().unwrap();
+    let mut ccs = vec![Cc::new([0u8; 1000])];
+    let size = allocated_bytes().unwrap() - before;
+    ccs.extend((0..9).map(|_| Cc::new([0u8; 1000])));
+    collect_cycles();
+    let live = live_bytes_after_last_collection().unwrap();
+    assert_eq!(allocated_bytes().unwrap(), live);
+
+    let executions = executions_count().unwrap();
+    while allocated_bytes().unwrap() + size <= 2 * live {
+        ccs.push(Cc::new([0u8; 1000]));
+    }
+    assert_eq!(executions, executions_count().unwrap());
+
+    ccs.push(Cc::new([0u8; 1000]));
+    ccs.push(Cc::new([0u8; 1000]));
+    assert_eq!(executions + 1, executions_count().unwrap());
+    assert_eq!(CollectionTrigger::BytesThreshold, last_collections().unwrap().last().unwrap().trigger());
+
+    config(|config| config.set_growth_strategy(GrowthStrategy::Doubling)).unwrap();
+    drop(ccs);
+}
+
+#[test]
+fn max_bytes_threshold() {
+    config(|config| config.set_max_bytes_threshold(4096)).unwrap();
+    collect_cycles(); // Apply the new maximum
+
+    let executions = executions_count().unwrap();
+    let ccs: Vec<_> = (0..6).map(|_| Cc::new([0u8; 1000])).collect();
+    assert_ne!(executions, executions_count().unwrap());
+
+    config(|config| config.set_max_bytes_threshold(usize::MAX)).unwrap();
+    drop(ccs);
+}
+
+#[test]
+#[should_panic(expected = "the minimum threshold must not be greater than the maximum threshold")]
+fn min_greater_than_max() {
+    let mut config = Config::default();
+    config.set_max_bytes_threshold(1000);
+    config.set_min_bytes_threshold(2000);
+}
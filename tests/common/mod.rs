@@ -0,0 +1,101 @@
+//! Fixtures shared by the integration tests.
+
+#![allow(dead_code)] // Every test module uses only some of the fixtures
+
+use std::cell::RefCell;
+
+use rust_cc::{Cc, Context, Finalize, Trace};
+#[cfg(feature = "auto-collect")]
+use rust_cc::config::{CollectionPolicy, Config, config, OutOfMemoryPolicy, PolicyContext, replace};
+#[cfg(feature = "auto-collect")]
+use rust_cc::state::allocated_bytes;
+
+/// Enables the global state of the critical-section backend before the tests are started, so that every test module
+/// including this one can be run with the critical-section feature.
+#[cfg(all(feature = "critical-section", not(feature = "std")))]
+#[used]
+#[cfg_attr(any(target_os = "linux", target_os = "android", target_os = "freebsd"), link_section = ".init_array")]
+#[cfg_attr(target_vendor = "apple", link_section = "__DATA,__mod_init_func")]
+#[cfg_attr(windows, link_section = ".CRT$XCU")]
+static INIT_GLOBAL_STATE: extern "C" fn() = {
+    extern "C" fn init() {
+        // SAFETY: tests are run serially when the critical-section feature is enabled (see test.yml)
+        unsafe { rust_cc::init_global_state() };
+    }
+    init
+};
+
+/// A node of a linked list, which can be used to build cycles.
+#[derive(Default)]
+pub struct Node {
+    pub next: RefCell<Option<Cc<Node>>>,
+}
+
+unsafe impl Trace for Node {
+    fn trace(&self, ctx: &mut Context<'_>) {
+        self.next.trace(ctx);
+    }
+}
+
+impl Finalize for Node {}
+
+#[track_caller]
+pub fn new_node() -> Cc<Node> {
+    Cc::new(Node::default())
+}
+
+/// A [`CollectionPolicy`] which starts a collection every time it's asked.
+#[cfg(feature = "auto-collect")]
+#[derive(Debug, Clone)]
+pub struct Always;
+
+#[cfg(feature = "auto-collect")]
+impl CollectionPolicy for Always {
+    fn should_collect(&mut self, _: &PolicyContext<'_>) -> bool {
+        true
+    }
+}
+
+/// Restores the configuration of the current thread when dropped, also when a test panics.
+#[cfg(feature = "auto-collect")]
+pub struct ConfigGuard {
+    old: Option<Config>,
+}
+
+#[cfg(feature = "auto-collect")]
+impl ConfigGuard {
+    /// Modifies the configuration using `f`, saving the previous one.
+    pub fn new(f: impl FnOnce(&mut Config)) -> ConfigGuard {
+        let old = config(|config| {
+            let old = config.clone();
+            f(config);
+            old
+        }).unwrap();
+        ConfigGuard { old: Some(old) }
+    }
+}
+
+#[cfg(feature = "auto-collect")]
+impl Drop for ConfigGuard {
+    fn drop(&mut self) {
+        if let Some(old) = self.old.take() {
+            let _ = replace(old);
+        }
+    }
+}
+
+/// Sets a heap limit which fits `nodes` more [`Node`]s and the provided [`OutOfMemoryPolicy`].
+/// Automatic collections are disabled, since the limit is enforced anyway.
+#[cfg(feature = "auto-collect")]
+pub fn limit_heap(nodes: usize, policy: OutOfMemoryPolicy) -> ConfigGuard {
+    let before = allocated_bytes().unwrap();
+    let node = new_node();
+    let size = allocated_bytes().unwrap() - before;
+    drop(node);
+
+    ConfigGuard::new(|config| {
+        config.set_max_heap_bytes(Some(allocated_bytes().unwrap() + nodes * size));
+        config.set_out_of_memory_policy(policy);
+        config.set_auto_collect(false);
+    })
+}
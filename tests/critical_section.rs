@@ -0,0 +1,46 @@
+#![cfg(all(feature = "critical-section", not(feature = "std")))]
+//! This module tests the global state used by the critical-section feature. Since it's shared by every test,
+//! tests must be run serially, with `--test-threads=1`. The state is enabled by the `common` module.
+
+use std::thread;
+
+use rust_cc::collect_cycles;
+use rust_cc::state::{allocated_bytes, buffered_objects_count};
+
+mod common;
+
+use common::new_node;
+
+fn new_cycle() {
+    let a = new_node();
+    let b = new_node();
+    *b.next.borrow_mut() = Some(a.clone());
+    *a.next.borrow_mut() = Some(b);
+}
+
+#[test]
+fn garbage_cycles_are_collected() {
+    collect_cycles();
+    let allocated = allocated_bytes().unwrap();
+
+    new_cycle();
+    assert!(allocated_bytes().unwrap() > allocated);
+    assert_eq!(1, buffered_objects_count().unwrap());
+
+    collect_cycles();
+    assert_eq!(allocated, allocated_bytes().unwrap());
+    assert_eq!(0, buffered_objects_count().unwrap());
+}
+
+#[test]
+fn state_is_shared_between_threads() {
+    collect_cycles();
+    let allocated = allocated_bytes().unwrap();
+
+    // The cycle is buffered into the global list, so it can be collected by another thread
+    thread::spawn(new_cycle).join().unwrap();
+    assert_eq!(1, buffered_objects_count().unwrap());
+
+    collect_cycles();
+    assert_eq!(allocated, allocated_bytes().unwrap());
+}
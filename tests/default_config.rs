@@ -0,0 +1,81 @@
+#![cfg(all(feature = "auto-collect", feature = "std"))]
+//! This module tests the process-wide configuration. Since it's shared by every test, tests are run serially.
+
+use std::num::NonZeroUsize;
+use std::sync::{Barrier, Mutex, MutexGuard, PoisonError};
+use std::sync::Arc;
+use std::thread;
+
+use rust_cc::config::{broadcast, config, Config, default_config, set_default};
+
+fn serial() -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn new_config(auto_collect: bool, buffered_threshold: usize) -> Config {
+    let mut config = Config::default();
+    config.set_auto_collect(auto_collect);
+    config.set_buffered_objects_threshold(NonZeroUsize::new(buffered_threshold));
+    config
+}
+
+fn read_config() -> (bool, Option<usize>) {
+    config(|config| {
+        (config.auto_collect(), config.buffered_objects_threshold().map(NonZeroUsize::get))
+    }).unwrap()
+}
+
+#[test]
+fn set_default_for_new_threads() {
+    let _serial = serial();
+
+    // Make sure this thread has already accessed its configuration
+    config(|config| config.set_auto_collect(true)).unwrap();
+
+    set_default(new_config(false, 5));
+
+    assert_eq!((false, Some(5)), thread::spawn(read_config).join().unwrap());
+
+    let default = default_config().unwrap();
+    assert!(!default.auto_collect());
+    assert_eq!(Some(5), default.buffered_objects_threshold().map(NonZeroUsize::get));
+
+    // The configuration of this thread must be unchanged
+    assert_eq!((true, None), read_config());
+
+    set_default(Config::default());
+}
+
+#[test]
+fn broadcast_to_running_threads() {
+    let _serial = serial();
+
+    set_default(Config::default());
+
+    let barrier = Arc::new(Barrier::new(2));
+    let handle = {
+        let barrier = barrier.clone();
+        thread::spawn(move || {
+            let before = read_config();
+            barrier.wait(); // Wait for the broadcast
+            barrier.wait();
+            (before, read_config())
+        })
+    };
+
+    barrier.wait();
+    broadcast(new_config(false, 7));
+    barrier.wait();
+
+    let (before, after) = handle.join().unwrap();
+    assert_eq!((true, None), before);
+    assert_eq!((false, Some(7)), after);
+
+    // The broadcast also applies to this thread and to new threads
+    assert_eq!((false, Some(7)), read_config());
+    assert_eq!((false, Some(7)), thread::spawn(read_config).join().unwrap());
+
+    broadcast(Config::default());
+    assert_eq!((true, None), read_config());
+}
@@ -1,6 +1,9 @@
 use std::cell::{Cell, RefCell};
 use rust_cc::*;
 
+#[path = "../common/mod.rs"]
+mod common;
+
 #[derive(Finalize)]
 struct ToTrace {
     has_been_traced: Cell<bool>,
@@ -0,0 +1,201 @@
+#![cfg(all(feature = "auto-collect", feature = "std"))]
+//! This module tests reading the configuration from the environment. Since the environment is shared by every test, tests are run serially.
+
+use std::env;
+use std::num::NonZeroUsize;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use rust_cc::config::{Config, DeferredCollection, EnvConfigError, from_env, GrowthStrategy, InvalidConfigError, OutOfMemoryPolicy};
+#[cfg(feature = "finalization")]
+use rust_cc::config::{FinalizationPolicy, LeftoverObjects};
+
+const VARIABLES: &[&str] = &[
+    "RUST_CC_AUTO_COLLECT",
+    "RUST_CC_BYTES_THRESHOLD",
+    "RUST_CC_ADJUSTMENT_PERCENT",
+    "RUST_CC_BUFFERED_THRESHOLD",
+    "RUST_CC_MIN_BYTES_THRESHOLD",
+    "RUST_CC_MAX_BYTES_THRESHOLD",
+    "RUST_CC_GROWTH_STRATEGY",
+    "RUST_CC_MAX_HEAP_BYTES",
+    "RUST_CC_OUT_OF_MEMORY_POLICY",
+    "RUST_CC_MAX_PAUSE_MICROS",
+    "RUST_CC_DEFERRED_COLLECTION",
+    #[cfg(feature = "finalization")]
+    "RUST_CC_FINALIZATION_MAX_ROUNDS",
+    #[cfg(feature = "finalization")]
+    "RUST_CC_FINALIZE_CREATED_OBJECTS",
+    #[cfg(feature = "finalization")]
+    "RUST_CC_LEFTOVER_OBJECTS",
+];
+
+/// Sets the provided environment variables. Since the environment is shared, the other tests are blocked until
+/// the guard is dropped, which removes every `RUST_CC_*` variable.
+struct EnvGuard {
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl EnvGuard {
+    fn set(vars: &[(&str, &str)]) -> EnvGuard {
+        static LOCK: Mutex<()> = Mutex::new(());
+        let guard = EnvGuard {
+            _lock: LOCK.lock().unwrap_or_else(PoisonError::into_inner),
+        };
+        for (variable, value) in vars {
+            env::set_var(variable, value);
+        }
+        guard
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        for variable in VARIABLES.iter() {
+            env::remove_var(variable);
+        }
+    }
+}
+
+#[test]
+fn empty_environment() {
+    let _guard = EnvGuard::set(&[]);
+    assert_eq!(Config::default().to_env_string(), from_env().unwrap().to_env_string());
+}
+
+#[test]
+fn read_variables() {
+    let _guard = EnvGuard::set(&[
+        ("RUST_CC_AUTO_COLLECT", "false"),
+        ("RUST_CC_ADJUSTMENT_PERCENT", "0.25"),
+        ("RUST_CC_BUFFERED_THRESHOLD", " 50 "),
+        ("RUST_CC_MAX_HEAP_BYTES", "1048576"),
+    ]);
+
+    let config = from_env().unwrap();
+    assert!(!config.auto_collect());
+    assert_eq!(0.25, config.adjustment_percent());
+    assert_eq!(NonZeroUsize::new(50), config.buffered_objects_threshold());
+    assert_eq!(Some(1048576), config.max_heap_bytes());
+}
+
+#[test]
+fn round_trip() {
+    let mut config = Config::default();
+    config.set_auto_collect(false);
+    config.set_adjustment_percent(0.5);
+    config.set_buffered_objects_threshold(NonZeroUsize::new(10));
+    config.set_max_bytes_threshold(1 << 20);
+    config.set_min_bytes_threshold(1 << 10);
+    config.set_growth_strategy(GrowthStrategy::Proportional { percent: 150 });
+    config.set_max_heap_bytes(Some(1 << 30));
+    config.set_out_of_memory_policy(OutOfMemoryPolicy::Error);
+    config.set_max_pause(Some(Duration::from_millis(5)));
+    config.set_deferred_collection(DeferredCollection::WaitForNextTrigger);
+    #[cfg(feature = "finalization")]
+    {
+        let mut policy = FinalizationPolicy::default();
+        policy.set_max_rounds(3);
+        policy.set_finalize_created_objects(true);
+        policy.set_leftover_objects(LeftoverObjects::DropWithoutFinalizing);
+        config.set_finalization_policy(policy);
+    }
+    let env = config.to_env_string();
+
+    let vars: Vec<(&str, &str)> = env.lines().map(|line| line.split_once('=').unwrap()).collect();
+    assert_eq!(VARIABLES.len(), vars.len());
+
+    let _guard = EnvGuard::set(&vars);
+    let read = from_env().unwrap();
+    assert_eq!(env, read.to_env_string());
+
+    assert!(!read.auto_collect());
+    assert_eq!(GrowthStrategy::Proportional { percent: 150 }, read.growth_strategy());
+    assert_eq!(Some(1 << 30), read.max_heap_bytes());
+    assert!(matches!(read.out_of_memory_policy(), OutOfMemoryPolicy::Error));
+    assert_eq!(Some(Duration::from_millis(5)), read.max_pause());
+    assert_eq!(DeferredCollection::WaitForNextTrigger, read.deferred_collection());
+    #[cfg(feature = "finalization")]
+    {
+        let policy = read.finalization_policy();
+        assert_eq!(3, policy.max_rounds());
+        assert!(policy.finalize_created_objects());
+        assert_eq!(LeftoverObjects::DropWithoutFinalizing, policy.leftover_objects());
+    }
+}
+
+#[test]
+fn callback_out_of_memory_policy_is_omitted() {
+    let mut config = Config::default();
+    config.set_out_of_memory_policy(OutOfMemoryPolicy::Callback(|_| {}));
+    let env = config.to_env_string();
+
+    assert!(!env.contains("RUST_CC_OUT_OF_MEMORY_POLICY"));
+    assert_eq!(VARIABLES.len() - 1, env.lines().count());
+}
+
+#[test]
+fn malformed_values() {
+    {
+        let _guard = EnvGuard::set(&[("RUST_CC_AUTO_COLLECT", "yes")]);
+        let error = from_env().unwrap_err();
+        assert!(matches!(&error, EnvConfigError::Malformed { variable: "RUST_CC_AUTO_COLLECT", value, .. } if value == "yes"));
+        assert!(error.to_string().contains("RUST_CC_AUTO_COLLECT"));
+    }
+
+    {
+        let _guard = EnvGuard::set(&[("RUST_CC_BYTES_THRESHOLD", "-1")]);
+        assert!(matches!(from_env(), Err(EnvConfigError::Malformed { variable: "RUST_CC_BYTES_THRESHOLD", .. })));
+    }
+
+    {
+        let _guard = EnvGuard::set(&[("RUST_CC_GROWTH_STRATEGY", "proportional")]);
+        assert!(matches!(from_env(), Err(EnvConfigError::Malformed { variable: "RUST_CC_GROWTH_STRATEGY", .. })));
+    }
+
+    #[cfg(feature = "finalization")]
+    {
+        let _guard = EnvGuard::set(&[("RUST_CC_FINALIZATION_MAX_ROUNDS", "0")]);
+        assert!(matches!(from_env(), Err(EnvConfigError::Malformed { variable: "RUST_CC_FINALIZATION_MAX_ROUNDS", .. })));
+    }
+
+    {
+        let _guard = EnvGuard::set(&[("RUST_CC_ADJUSTMENT_PERCENT", "1.5")]);
+        assert_eq!(Err(EnvConfigError::Invalid(InvalidConfigError::AdjustmentPercent(1.5))), from_env().map(|_| ()));
+    }
+}
+
+#[cfg(feature = "env-config")]
+#[test]
+fn applied_on_first_access() {
+    use rust_cc::config::config;
+
+    let _guard = EnvGuard::set(&[("RUST_CC_BUFFERED_THRESHOLD", "42")]);
+
+    let threshold = std::thread::spawn(|| {
+        config(|config| config.buffered_objects_threshold()).unwrap()
+    }).join().unwrap();
+    assert_eq!(NonZeroUsize::new(42), threshold);
+}
+
+#[cfg(feature = "env-config")]
+#[test]
+fn malformed_variables_are_not_applied() {
+    use rust_cc::config::{config, env_error};
+
+    let _guard = EnvGuard::set(&[
+        ("RUST_CC_BUFFERED_THRESHOLD", "42"),
+        ("RUST_CC_AUTO_COLLECT", "yes"),
+    ]);
+
+    let (threshold, error) = std::thread::spawn(|| {
+        // Accessing the configuration doesn't panic and none of the variables is applied
+        let threshold = config(|config| config.buffered_objects_threshold()).unwrap();
+        (threshold, env_error())
+    }).join().unwrap();
+    assert_eq!(None, threshold);
+    assert!(matches!(error, Some(EnvConfigError::Malformed { variable: "RUST_CC_AUTO_COLLECT", .. })));
+
+    let error = std::thread::spawn(env_error).join().unwrap();
+    assert!(error.is_some());
+}
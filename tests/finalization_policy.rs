@@ -0,0 +1,117 @@
+#![cfg(all(feature = "auto-collect", feature = "finalization"))]
+
+use std::cell::{Cell, RefCell};
+
+use rust_cc::{Cc, collect_cycles, Context, Finalize, Trace};
+use rust_cc::config::{FinalizationPolicy, LeftoverObjects};
+use rust_cc::state::buffered_objects_count;
+
+mod common;
+
+use common::ConfigGuard;
+
+thread_local! {
+    static FINALIZED: Cell<usize> = const { Cell::new(0) };
+    static DROPPED: Cell<usize> = const { Cell::new(0) };
+}
+
+fn finalized() -> usize {
+    FINALIZED.with(Cell::get)
+}
+
+fn dropped() -> usize {
+    DROPPED.with(Cell::get)
+}
+
+/// Creates a new garbage [`Spawner`] every time it is finalized.
+struct Spawner {
+    cycle: RefCell<Option<Cc<Spawner>>>,
+}
+
+unsafe impl Trace for Spawner {
+    fn trace(&self, ctx: &mut Context<'_>) {
+        self.cycle.trace(ctx);
+    }
+}
+
+impl Finalize for Spawner {
+    fn finalize(&self) {
+        FINALIZED.with(|finalized| finalized.set(finalized.get() + 1));
+        new_garbage_spawner();
+    }
+}
+
+impl Drop for Spawner {
+    fn drop(&mut self) {
+        DROPPED.with(|dropped| dropped.set(dropped.get() + 1));
+    }
+}
+
+fn new_garbage_spawner() {
+    let spawner = Cc::new(Spawner { cycle: RefCell::new(None) });
+    *spawner.cycle.borrow_mut() = Some(spawner.clone());
+}
+
+/// Sets the finalization policy. Automatic collections are disabled, so that only manual collections are executed.
+fn set_policy(policy: FinalizationPolicy) -> ConfigGuard {
+    ConfigGuard::new(|config| {
+        config.set_finalization_policy(policy);
+        config.set_auto_collect(false);
+    })
+}
+
+#[test]
+fn created_objects_are_not_finalized_by_default() {
+    let _guard = set_policy(FinalizationPolicy::default());
+    let (finalized, dropped) = (finalized(), dropped());
+
+    new_garbage_spawner();
+    let report = collect_cycles().unwrap();
+
+    assert_eq!(2, report.iterations());
+    assert_eq!(finalized + 1, self::finalized());
+    assert_eq!(dropped + 2, self::dropped());
+    assert_eq!(0, buffered_objects_count().unwrap());
+}
+
+#[test]
+fn leftover_objects_are_kept() {
+    let mut policy = FinalizationPolicy::default();
+    policy.set_max_rounds(3);
+    policy.set_finalize_created_objects(true);
+    let _guard = set_policy(policy);
+    let (finalized, dropped) = (finalized(), dropped());
+
+    new_garbage_spawner();
+    let report = collect_cycles().unwrap();
+
+    assert_eq!(3, report.iterations());
+    assert_eq!(finalized + 3, self::finalized());
+    assert_eq!(dropped, self::dropped());
+    assert_ne!(0, buffered_objects_count().unwrap());
+}
+
+#[test]
+fn leftover_objects_are_dropped() {
+    let mut policy = FinalizationPolicy::default();
+    policy.set_max_rounds(3);
+    policy.set_finalize_created_objects(true);
+    policy.set_leftover_objects(LeftoverObjects::DropWithoutFinalizing);
+    let _guard = set_policy(policy);
+    let (finalized, dropped) = (finalized(), dropped());
+
+    new_garbage_spawner();
+    let report = collect_cycles().unwrap();
+
+    // The last spawner is dropped without being finalized
+    assert_eq!(4, report.iterations());
+    assert_eq!(finalized + 3, self::finalized());
+    assert_eq!(dropped + 4, self::dropped());
+    assert_eq!(0, buffered_objects_count().unwrap());
+}
+
+#[test]
+#[should_panic(expected = "the maximum number of rounds must be greater than 0")]
+fn zero_rounds() {
+    FinalizationPolicy::default().set_max_rounds(0);
+}
@@ -0,0 +1,81 @@
+#![cfg(feature = "global-stats")]
+
+use std::sync::{Arc, Barrier};
+use std::thread::{self, ThreadId};
+
+use rust_cc::{Cc, collect_cycles};
+use rust_cc::state::{allocated_bytes, buffered_objects_count, executions_count, global_stats, StatsSnapshot};
+
+mod common;
+
+use common::new_node;
+
+fn thread_stats(id: ThreadId) -> Option<StatsSnapshot> {
+    global_stats().threads().iter().find(|thread| thread.thread_id() == id).map(|thread| thread.stats())
+}
+
+#[test]
+fn mirrors_thread_stats() {
+    let cc = new_node();
+    *cc.next.borrow_mut() = Some(cc.clone());
+    let _other = cc.clone();
+    drop(cc);
+
+    let stats = thread_stats(thread::current().id()).expect("Thread not registered");
+    assert_eq!(allocated_bytes().unwrap(), stats.allocated_bytes());
+    assert_eq!(executions_count().unwrap(), stats.executions_count());
+    assert_eq!(buffered_objects_count().unwrap(), stats.buffered_objects_count());
+    assert_eq!(1, stats.buffered_objects_count());
+
+    drop(_other);
+    collect_cycles();
+
+    let stats = thread_stats(thread::current().id()).expect("Thread not registered");
+    assert_eq!(allocated_bytes().unwrap(), stats.allocated_bytes());
+    assert_eq!(executions_count().unwrap(), stats.executions_count());
+    assert_eq!(0, stats.buffered_objects_count());
+}
+
+#[test]
+fn aggregates_threads() {
+    const THREADS: usize = 4;
+
+    let allocated = Arc::new(Barrier::new(THREADS + 1));
+    let checked = Arc::new(Barrier::new(THREADS + 1));
+
+    let handles: Vec<_> = (0..THREADS).map(|i| {
+        let allocated = allocated.clone();
+        let checked = checked.clone();
+        thread::Builder::new().name(format!("stats-{i}")).spawn(move || {
+            let _cc = Cc::new([0u8; 64]);
+            allocated.wait();
+            checked.wait();
+        }).unwrap()
+    }).collect();
+
+    allocated.wait();
+
+    let stats = global_stats();
+    let ids: Vec<ThreadId> = handles.iter().map(|handle| handle.thread().id()).collect();
+    let threads: Vec<_> = stats.threads().iter().filter(|thread| ids.contains(&thread.thread_id())).collect();
+    assert_eq!(THREADS, threads.len());
+
+    for thread in &threads {
+        assert!(thread.thread_name().unwrap().starts_with("stats-"));
+        assert!(thread.stats().allocated_bytes() >= 64);
+    }
+
+    let sum: usize = stats.threads().iter().map(|thread| thread.stats().allocated_bytes()).sum();
+    assert_eq!(sum, stats.total().allocated_bytes());
+
+    checked.wait();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    // Exited threads must be deregistered
+    for id in ids {
+        assert!(thread_stats(id).is_none());
+    }
+}
@@ -0,0 +1,486 @@
+#![cfg(feature = "heap-introspection")]
+
+use std::cell::RefCell;
+
+use rust_cc::{Cc, collect_cycles, Context, Finalize, Trace};
+use rust_cc::state::{buffered_objects_count, explain_retention};
+use rust_cc::introspection::{CcRef, collect_cycles_with_diagnostics, heap_dump, HeapDump, HeapSnapshot, IntrospectionError, ObjectInfo, walk_reachable};
+
+mod common;
+
+use common::{new_node, Node};
+
+// When critical-section is enabled the state is shared between every test, so only consider Nodes
+fn nodes(dump: &HeapDump) -> Vec<(usize, &ObjectInfo)> {
+    dump.objects().iter().enumerate().filter(|(_, object)| object.type_name().ends_with("Node")).collect()
+}
+
+#[test]
+fn objects_and_edges() {
+    let a = new_node();
+    let b = new_node();
+    *a.next.borrow_mut() = Some(b.clone());
+    *b.next.borrow_mut() = Some(a.clone());
+    drop(b);
+
+    let dump = heap_dump().unwrap();
+    let nodes = nodes(&dump);
+    assert_eq!(2, nodes.len());
+
+    // a is referenced by the local variable and by b
+    let (a_index, a_info) = nodes.iter().copied().find(|(_, object)| object.strong_count() == 2).unwrap();
+    let (b_index, b_info) = nodes.iter().copied().find(|(_, object)| object.strong_count() == 1).unwrap();
+
+    assert_eq!(&[b_index], a_info.edges());
+    assert_eq!(&[a_index], b_info.edges());
+    assert!(a_info.size() >= size_of::<Node>());
+    assert_eq!(a_info.size(), b_info.size());
+
+    // Break the cycle
+    *a.next.borrow_mut() = None;
+}
+
+#[test]
+fn freed_objects_are_removed() {
+    let a = new_node();
+    *a.next.borrow_mut() = Some(a.clone());
+    assert_eq!(1, nodes(&heap_dump().unwrap()).len());
+
+    drop(a);
+    assert_eq!(1, nodes(&heap_dump().unwrap()).len()); // Not collected yet
+
+    collect_cycles();
+    assert!(nodes(&heap_dump().unwrap()).is_empty());
+}
+
+#[test]
+fn cannot_dump_while_dropping() {
+    struct Dumping;
+
+    unsafe impl Trace for Dumping {
+        fn trace(&self, _: &mut Context<'_>) {}
+    }
+
+    impl Finalize for Dumping {}
+
+    impl Drop for Dumping {
+        fn drop(&mut self) {
+            assert!(matches!(heap_dump(), Err(IntrospectionError::Collecting)));
+        }
+    }
+
+    drop(Cc::new(Dumping));
+}
+
+#[test]
+fn dot_and_json() {
+    let a = new_node();
+    *a.next.borrow_mut() = Some(a.clone());
+
+    let dump = heap_dump().unwrap();
+    let (index, _) = nodes(&dump)[0];
+
+    let dot = dump.to_dot();
+    assert!(dot.starts_with("digraph heap {"));
+    assert!(dot.contains(&format!("n{index} -> n{index};")));
+
+    let json = dump.to_json();
+    assert!(json.starts_with("{\"objects\":["));
+    assert!(json.contains(&format!("\"edges\":[{index}]")));
+    assert!(json.contains("common::Node"));
+
+    *a.next.borrow_mut() = None;
+}
+
+#[test]
+fn diagnostics_garbage_cycles() {
+    struct Holder {
+        cyclic: RefCell<Option<Cc<Holder>>>,
+        _acyclic: Cc<u64>,
+    }
+
+    unsafe impl Trace for Holder {
+        fn trace(&self, ctx: &mut Context<'_>) {
+            self.cyclic.trace(ctx);
+            self._acyclic.trace(ctx);
+        }
+    }
+
+    impl Finalize for Holder {}
+
+    // A cycle of three nodes
+    let a = new_node();
+    let b = new_node();
+    let c = new_node();
+    *a.next.borrow_mut() = Some(b.clone());
+    *b.next.borrow_mut() = Some(c.clone());
+    *c.next.borrow_mut() = Some(a.clone());
+
+    // A self-referencing object pointing to an acyclic one
+    let holder = Cc::new(Holder {
+        cyclic: RefCell::new(None),
+        _acyclic: Cc::new(5),
+    });
+    *holder.cyclic.borrow_mut() = Some(holder.clone());
+
+    // Drop everything only after allocating, since allocations may start a collection
+    drop((a, b, c, holder));
+
+    let diagnostics = collect_cycles_with_diagnostics().unwrap();
+    assert_eq!(5, diagnostics.report().dropped());
+    assert!(diagnostics.roots().is_empty());
+
+    let mut cycles: Vec<_> = diagnostics.garbage_cycles().iter().map(|cycle| cycle.members()).collect();
+    cycles.sort_by_key(|members| members.len());
+    assert_eq!(3, cycles.len());
+
+    assert!(cycles[0][0].type_name().ends_with("u64"));
+    assert!(cycles[0][0].edges().is_empty());
+
+    assert!(cycles[1][0].type_name().ends_with("Holder"));
+    assert_eq!(&[0], cycles[1][0].edges());
+
+    assert_eq!(3, cycles[2].len());
+    for member in cycles[2] {
+        assert!(member.type_name().ends_with("Node"));
+        assert!(member.size() >= size_of::<Node>());
+        assert_eq!(1, member.edges().len());
+    }
+}
+
+#[test]
+fn diagnostics_roots() {
+    let a = new_node();
+    let b = new_node();
+    *a.next.borrow_mut() = Some(b.clone());
+    *b.next.borrow_mut() = Some(a.clone());
+    drop(b);
+
+    let diagnostics = collect_cycles_with_diagnostics().unwrap();
+    assert!(diagnostics.garbage_cycles().is_empty());
+
+    // a is kept alive by the local variable
+    let roots = diagnostics.roots();
+    assert_eq!(1, roots.len());
+    assert!(roots[0].type_name().ends_with("Node"));
+    assert_eq!(2, roots[0].strong_count());
+    assert_eq!(1, roots[0].external_count());
+
+    // Diagnostics are recorded only when requested
+    drop(a.next.borrow_mut().take());
+    assert!(collect_cycles().is_some());
+}
+
+#[test]
+fn retention_chain() {
+    let a = new_node();
+    let b = new_node();
+    let c = new_node();
+    *a.next.borrow_mut() = Some(b.clone());
+    *b.next.borrow_mut() = Some(c.clone());
+    drop(b);
+
+    // c is kept alive by a through b
+    let explanation = explain_retention(&c).unwrap();
+    assert!(!explanation.is_garbage());
+
+    let chain = explanation.chain();
+    assert_eq!(3, chain.len());
+    assert_eq!(1, chain[0].external_count());
+    assert_eq!(0, chain[1].external_count());
+    assert_eq!(0, chain[2].external_count()); // The reference passed to explain_retention isn't counted
+    assert_eq!(2, chain[2].strong_count());
+    assert!(chain.iter().all(|retainer| retainer.type_name().ends_with("Node")));
+
+    // c is directly referenced
+    let c2 = c.clone();
+    let chain = explain_retention(&c).unwrap().chain().len();
+    assert_eq!(1, chain);
+    drop(c2);
+
+    drop(a);
+    assert!(explain_retention(&c).unwrap().is_garbage());
+}
+
+#[test]
+fn retention_by_garbage_cycle() {
+    let a = new_node();
+    let b = new_node();
+    *a.next.borrow_mut() = Some(b.clone());
+    *b.next.borrow_mut() = Some(a.clone());
+    drop(b);
+
+    // a is externally referenced only by the reference passed to explain_retention
+    assert!(explain_retention(&a).unwrap().is_garbage());
+
+    let a2 = a.clone();
+    let explanation = explain_retention(&a).unwrap();
+    assert_eq!(1, explanation.chain().len());
+    assert_eq!(1, explanation.chain()[0].external_count());
+    drop(a2);
+
+    *a.next.borrow_mut() = None;
+}
+
+#[test]
+fn reachable_and_retained_size() {
+    // a -> b -> c, and d -> c
+    let a = new_node();
+    let b = new_node();
+    let c = new_node();
+    let d = new_node();
+    *a.next.borrow_mut() = Some(b.clone());
+    *b.next.borrow_mut() = Some(c.clone());
+    *d.next.borrow_mut() = Some(c.clone());
+    drop(b);
+    let node_size = new_node().reachable_size();
+
+    assert_eq!(3 * node_size, a.reachable_size());
+    assert_eq!(2 * node_size, d.reachable_size());
+
+    // c is also referenced by d and by the local variable
+    assert_eq!(2 * node_size, a.retained_size());
+    drop(c);
+    assert_eq!(2 * node_size, a.retained_size()); // d still points to c
+    drop(d);
+    assert_eq!(3 * node_size, a.retained_size());
+
+    // Retained objects are not retained if the root is referenced from elsewhere
+    let a2 = a.clone();
+    assert_eq!(0, a.retained_size());
+    drop(a2);
+}
+
+#[test]
+fn retained_cycle() {
+    let a = new_node();
+    let b = new_node();
+    *a.next.borrow_mut() = Some(b.clone());
+    *b.next.borrow_mut() = Some(a.clone());
+    drop(b);
+
+    let size = a.reachable_size();
+    assert_eq!(size, a.retained_size()); // Both a and b would be collected
+
+    *a.next.borrow_mut() = None;
+}
+
+#[test]
+fn retained_size_panics_while_dropping() {
+    struct Measuring;
+
+    unsafe impl Trace for Measuring {
+        fn trace(&self, _: &mut Context<'_>) {}
+    }
+
+    impl Finalize for Measuring {}
+
+    thread_local! {
+        static OTHER: Cc<Node> = new_node();
+    }
+
+    impl Drop for Measuring {
+        fn drop(&mut self) {
+            let res = std::panic::catch_unwind(|| OTHER.with(|other| other.retained_size()));
+            assert!(res.is_err());
+        }
+    }
+
+    drop(Cc::new(Measuring));
+}
+
+#[test]
+fn visit_edges() {
+    let a = new_node();
+    let b = new_node();
+    *a.next.borrow_mut() = Some(b.clone());
+
+    let mut children = Vec::new();
+    a.visit_edges(|child| children.push(child.address()));
+    assert_eq!(1, children.len());
+
+    b.visit_edges(|_| panic!("b has no edges"));
+
+    a.visit_edges(|child| {
+        assert_eq!(2, child.strong_count()); // The local variable b and a
+        assert!(child.downcast_ref::<Node>().is_some());
+        assert!(child.downcast_ref::<u32>().is_none());
+        assert!(child.size() >= size_of::<Node>());
+        assert!(child.type_name().ends_with("Node"));
+        child.visit_edges(|_| panic!("b has no edges"));
+    });
+}
+
+#[test]
+fn visitor_can_drop_objects() {
+    let a = new_node();
+    let b = new_node();
+    *a.next.borrow_mut() = Some(b.clone());
+    drop(b);
+
+    // The CcRef keeps b alive
+    a.visit_edges(|child: CcRef<'_>| {
+        *a.next.borrow_mut() = None;
+        assert_eq!(0, child.strong_count());
+        assert!(child.downcast_ref::<Node>().unwrap().next.borrow().is_none());
+    });
+    assert!(nodes(&heap_dump().unwrap()).len() == 1);
+}
+
+#[test]
+fn visiting_doesnt_buffer_objects() {
+    let a = new_node();
+    let b = new_node();
+    *a.next.borrow_mut() = Some(b.clone());
+    *b.next.borrow_mut() = Some(new_node());
+    collect_cycles(); // Empty the buffer
+
+    let buffered = buffered_objects_count().unwrap();
+    walk_reachable(&a, |_| {});
+    a.visit_edges(|child| child.visit_edges(|_| {}));
+    assert_eq!(buffered, buffered_objects_count().unwrap());
+}
+
+#[test]
+fn collection_while_visiting() {
+    let a = new_node();
+    let mut b = Some(new_node());
+    *a.next.borrow_mut() = b.clone();
+    *b.as_ref().unwrap().next.borrow_mut() = b.clone();
+
+    a.visit_edges(|_| {
+        *a.next.borrow_mut() = None;
+        drop(b.take());
+        // b is kept alive only by the CcRef and by itself, so the collection removes it from the buffer
+        collect_cycles();
+    });
+
+    // The garbage cycle is still collected
+    collect_cycles();
+    assert_eq!(1, nodes(&heap_dump().unwrap()).len());
+}
+
+#[test]
+fn walk_reachable_visits_once() {
+    // A cycle, which would never end if walked without remembering the visited objects
+    let first = new_node();
+    let mut last = first.clone();
+    for _ in 0..1000 {
+        let node = new_node();
+        *last.next.borrow_mut() = Some(node.clone());
+        last = node;
+    }
+    *last.next.borrow_mut() = Some(first.clone());
+    drop(last);
+
+    let mut visited = Vec::new();
+    walk_reachable(&first, |node| visited.push(node.address()));
+    assert_eq!(1001, visited.len());
+    visited.sort_unstable();
+    visited.dedup();
+    assert_eq!(1001, visited.len());
+
+    // The first visited object is the root
+    let mut first_visited = None;
+    walk_reachable(&first, |node| {
+        first_visited.get_or_insert(node.address());
+    });
+    first.visit_edges(|child| assert_ne!(Some(child.address()), first_visited));
+
+    drop(first);
+    collect_cycles();
+}
+
+#[test]
+fn snapshot_diff() {
+    let before = HeapSnapshot::capture().unwrap();
+
+    let kept = new_node();
+    let garbage = new_node();
+    *garbage.next.borrow_mut() = Some(garbage.clone());
+    drop(garbage);
+
+    let diff = HeapSnapshot::capture().unwrap().diff(&before);
+    assert!(!diff.is_empty());
+    assert!(diff.freed().is_empty());
+    assert!(diff.grown().is_empty());
+    assert_eq!(1, diff.allocated().len());
+    assert!(diff.allocated()[0].type_name().ends_with("Node"));
+    assert_eq!(2, diff.allocated()[0].count());
+    assert_eq!(2 * kept.reachable_size(), diff.allocated()[0].bytes());
+    assert!(diff.to_string().contains("2 x "));
+
+    collect_cycles();
+    let middle = HeapSnapshot::capture().unwrap();
+    assert_eq!(1, middle.diff(&before).allocated()[0].count());
+
+    drop(kept);
+    let after = HeapSnapshot::capture().unwrap();
+    assert!(after.diff(&before).is_empty(), "{}", after.diff(&before));
+    assert_eq!(before.objects_count(), after.objects_count());
+    assert_eq!(before.bytes(), after.bytes());
+
+    let diff = after.diff(&middle);
+    assert_eq!(1, diff.freed().len());
+    assert_eq!(1, diff.freed()[0].count());
+}
+
+#[test]
+fn snapshot_reused_address() {
+    let cc = Cc::new(1u64);
+    let before = HeapSnapshot::capture().unwrap();
+    drop(cc);
+
+    // The allocator will likely reuse the same address
+    let _cc = Cc::new(2u64);
+    let diff = HeapSnapshot::capture().unwrap().diff(&before);
+    assert_eq!(1, diff.allocated()[0].count());
+    assert_eq!(1, diff.freed()[0].count());
+}
+
+#[test]
+fn snapshot_grown_objects() {
+    struct Cache {
+        entries: RefCell<Vec<Cc<u32>>>,
+    }
+
+    unsafe impl Trace for Cache {
+        fn trace(&self, ctx: &mut Context<'_>) {
+            self.entries.trace(ctx);
+        }
+    }
+
+    impl Finalize for Cache {}
+
+    let cache = Cc::new(Cache {
+        entries: RefCell::new(vec![Cc::new(0)]),
+    });
+    let before = HeapSnapshot::capture().unwrap();
+
+    cache.entries.borrow_mut().extend((1..4).map(Cc::new));
+
+    let diff = HeapSnapshot::capture().unwrap().diff(&before);
+    assert_eq!(3, diff.allocated()[0].count());
+
+    let grown = diff.grown();
+    assert_eq!(1, grown.len());
+    assert!(grown[0].type_name().ends_with("Cache"));
+    assert_eq!(1, grown[0].previous_edges());
+    assert_eq!(4, grown[0].edges());
+    assert!(diff.to_string().contains("(1 -> 4 edges)"));
+}
+
+#[cfg(feature = "weak-ptr")]
+#[test]
+fn weak_count() {
+    use rust_cc::weak::WeakableCc;
+
+    let cc: WeakableCc<u32> = Cc::new_weakable(5);
+    let weak1 = cc.downgrade();
+    let _weak2 = weak1.clone();
+
+    let dump = heap_dump().unwrap();
+    let info = dump.objects().iter().find(|object| object.type_name().contains("Weakable<u32>")).unwrap();
+    assert_eq!(1, info.strong_count());
+    assert_eq!(2, info.weak_count());
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "auto-collect")]
+
+use std::cell::RefCell;
+
+use rust_cc::Cc;
+use rust_cc::config::{HeapLimitError, OutOfMemoryPolicy};
+use rust_cc::state::{CollectionTrigger, last_collections, report_external_allocation};
+
+mod common;
+
+use common::{limit_heap, new_node, Node};
+
+#[test]
+fn collection_is_forced() {
+    let _guard = limit_heap(2, OutOfMemoryPolicy::Panic);
+
+    // Fill the heap with garbage
+    let a = new_node();
+    let b = new_node();
+    *a.next.borrow_mut() = Some(b.clone());
+    *b.next.borrow_mut() = Some(a.clone());
+    drop(a);
+    drop(b);
+
+    let _c = new_node();
+    assert_eq!(CollectionTrigger::HeapLimit, last_collections().unwrap().last().unwrap().trigger());
+}
+
+#[test]
+fn try_new_returns_error() {
+    let _guard = limit_heap(2, OutOfMemoryPolicy::Error);
+
+    let _a = Cc::try_new(Node::default()).unwrap();
+    let _b = Cc::try_new(Node::default()).unwrap();
+
+    let error: HeapLimitError = Cc::try_new(Node::default()).err().unwrap();
+    assert!(error.allocated() + error.requested() > error.limit());
+}
+
+#[test]
+fn external_bytes_count_toward_the_limit() {
+    let _guard = limit_heap(2, OutOfMemoryPolicy::Error);
+
+    let _a = Cc::try_new(Node::default()).unwrap();
+
+    let external = (2 * size_of::<Node>()) as isize;
+    report_external_allocation(external).unwrap();
+    let error = Cc::try_new(Node::default()).err();
+    report_external_allocation(-external).unwrap();
+
+    let error = error.expect("External bytes not counted");
+    assert!(error.allocated() + error.requested() > error.limit());
+    let _b = Cc::try_new(Node::default()).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "would exceed the heap limit")]
+fn new_panics() {
+    let _guard = limit_heap(1, OutOfMemoryPolicy::Error);
+
+    let _a = new_node();
+    let _b = new_node();
+}
+
+#[test]
+#[should_panic(expected = "would exceed the heap limit")]
+fn try_new_panics_with_panic_policy() {
+    let _guard = limit_heap(1, OutOfMemoryPolicy::Panic);
+
+    let _a = new_node();
+    let _ = Cc::try_new(Node::default());
+}
+
+thread_local! {
+    static CACHE: RefCell<Vec<Cc<Node>>> = const { RefCell::new(Vec::new()) };
+}
+
+#[test]
+fn callback_frees_memory() {
+    fn clear_cache(_: HeapLimitError) {
+        CACHE.with(|cache| cache.borrow_mut().clear());
+    }
+
+    let _guard = limit_heap(2, OutOfMemoryPolicy::Callback(clear_cache));
+
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        cache.push(new_node());
+        cache.push(new_node());
+    });
+
+    let _a = new_node();
+    assert!(CACHE.with(|cache| cache.borrow().is_empty()));
+}
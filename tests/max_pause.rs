@@ -0,0 +1,158 @@
+#![cfg(all(feature = "auto-collect", feature = "std"))]
+
+use std::cell::{Cell, RefCell};
+use std::time::Duration;
+
+use rust_cc::{Cc, collect_cycles, Context, Finalize, Trace};
+use rust_cc::config::config;
+use rust_cc::state::{buffered_objects_count, CollectionTrigger, last_collections};
+
+mod common;
+
+use common::{Always, ConfigGuard};
+
+thread_local! {
+    static DROPPED: Cell<usize> = const { Cell::new(0) };
+}
+
+struct Node {
+    next: RefCell<Option<Cc<Node>>>,
+}
+
+unsafe impl Trace for Node {
+    fn trace(&self, ctx: &mut Context<'_>) {
+        self.next.trace(ctx);
+    }
+}
+
+impl Finalize for Node {}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        DROPPED.with(|dropped| dropped.set(dropped.get() + 1));
+    }
+}
+
+fn new_garbage_cycle() {
+    let node = Cc::new(Node { next: RefCell::new(None) });
+    *node.next.borrow_mut() = Some(node.clone());
+}
+
+#[test]
+fn interrupted_collections_are_resumed() {
+    let _guard = ConfigGuard::new(|config| config.set_auto_collect(false));
+
+    // Measure the time spent per traced object
+    new_garbage_cycle();
+    collect_cycles();
+
+    for _ in 0..3 {
+        new_garbage_cycle();
+    }
+    assert_eq!(3, buffered_objects_count().unwrap());
+    let dropped = DROPPED.with(Cell::get);
+
+    // With a zero pause only one buffered object is processed by every collection
+    config(|config| {
+        config.set_max_pause(Some(Duration::ZERO));
+        config.set_policy(Box::new(Always));
+        config.set_auto_collect(true);
+    }).unwrap();
+
+    let mut ccs = vec![Cc::new(0)];
+    let first = *last_collections().unwrap().last().unwrap();
+    assert_eq!(CollectionTrigger::Policy, first.trigger());
+    assert_eq!(Some(Duration::ZERO), first.pause_target());
+    assert_eq!(1, first.candidates());
+    assert!(first.interrupted());
+
+    let mut resumed = 0;
+    while last_collections().unwrap().last().unwrap().interrupted() {
+        ccs.push(Cc::new(0));
+        let report = *last_collections().unwrap().last().unwrap();
+        assert_eq!(CollectionTrigger::Resumed, report.trigger());
+        assert_eq!(1, report.candidates());
+        resumed += 1;
+    }
+    assert!(resumed >= 2);
+
+    assert_eq!(0, buffered_objects_count().unwrap());
+    assert_eq!(dropped + 3, DROPPED.with(Cell::get));
+}
+
+#[test]
+fn manual_collections_are_not_limited() {
+    let _guard = ConfigGuard::new(|config| config.set_auto_collect(false));
+
+    new_garbage_cycle();
+    collect_cycles();
+
+    config(|config| config.set_max_pause(Some(Duration::ZERO))).unwrap();
+    for _ in 0..3 {
+        new_garbage_cycle();
+    }
+
+    let report = collect_cycles().unwrap();
+    assert_eq!(None, report.pause_target());
+    assert!(!report.interrupted());
+    assert_eq!(0, buffered_objects_count().unwrap());
+}
+
+#[cfg(feature = "finalization")]
+#[test]
+fn resurrections_are_counted_across_interrupted_collections() {
+    thread_local! {
+        static RESURRECTED: RefCell<Option<Cc<Resurrecting>>> = const { RefCell::new(None) };
+    }
+
+    struct Resurrecting {
+        cyclic: RefCell<Option<Cc<Resurrecting>>>,
+    }
+
+    unsafe impl Trace for Resurrecting {
+        fn trace(&self, ctx: &mut Context<'_>) {
+            self.cyclic.trace(ctx);
+        }
+    }
+
+    impl Finalize for Resurrecting {
+        fn finalize(&self) {
+            RESURRECTED.with(|res| *res.borrow_mut() = self.cyclic.borrow().clone());
+        }
+    }
+
+    let _guard = ConfigGuard::new(|config| config.set_auto_collect(false));
+
+    // Measure the time spent per traced object
+    new_garbage_cycle();
+    collect_cycles();
+
+    let cc = Cc::new(Resurrecting {
+        cyclic: RefCell::new(None),
+    });
+    *cc.cyclic.borrow_mut() = Some(cc.clone());
+    drop(cc);
+
+    config(|config| {
+        config.set_max_pause(Some(Duration::ZERO));
+        config.set_policy(Box::new(Always));
+        config.set_auto_collect(true);
+    }).unwrap();
+
+    // The first collection finalizes the object, which is checked again by the resumed one
+    let mut ccs = vec![Cc::new(0)];
+    let first = *last_collections().unwrap().last().unwrap();
+    assert_eq!(1, first.finalized());
+    assert!(first.interrupted());
+
+    ccs.push(Cc::new(0));
+    let resumed = *last_collections().unwrap().last().unwrap();
+    assert_eq!(CollectionTrigger::Resumed, resumed.trigger());
+    assert!(!resumed.interrupted());
+    assert_eq!(1, first.resurrected() + resumed.resurrected());
+    assert_eq!(0, resumed.freed());
+
+    // Break the cycle and collect
+    let cc = RESURRECTED.with(|res| res.borrow_mut().take()).unwrap();
+    *cc.cyclic.borrow_mut() = None;
+}
@@ -0,0 +1,107 @@
+#![cfg(feature = "auto-collect")]
+
+use rust_cc::{Cc, collect_cycles, no_collect, NoCollectGuard};
+use rust_cc::config::{config, DeferredCollection, OutOfMemoryPolicy};
+use rust_cc::state::{CollectionTrigger, executions_count, last_collections};
+
+mod common;
+
+use common::{Always, ConfigGuard, limit_heap, new_node, Node};
+
+/// Makes every allocation start a collection.
+fn always_collect(deferred_collection: DeferredCollection) -> ConfigGuard {
+    ConfigGuard::new(|config| {
+        config.set_policy(Box::new(Always));
+        config.set_deferred_collection(deferred_collection);
+    })
+}
+
+#[test]
+fn collections_are_suppressed() {
+    let _guard = always_collect(DeferredCollection::RunImmediately);
+
+    let executions = executions_count().unwrap();
+    let _a = Cc::new(0);
+    assert_eq!(executions + 1, executions_count().unwrap());
+
+    let executions = executions_count().unwrap();
+    no_collect(|| {
+        let _b = Cc::new(1);
+        let _c = Cc::new(2);
+        assert_eq!(executions, executions_count().unwrap());
+
+        // Manual collections are still executed
+        collect_cycles();
+        assert_eq!(executions + 1, executions_count().unwrap());
+        let _d = Cc::new(3);
+    });
+
+    // The deferred collection is run when the suppression ends
+    assert_eq!(executions + 2, executions_count().unwrap());
+    assert_eq!(CollectionTrigger::Policy, last_collections().unwrap().last().unwrap().trigger());
+}
+
+#[test]
+fn nested_guards() {
+    let _guard = always_collect(DeferredCollection::RunImmediately);
+
+    let executions = executions_count().unwrap();
+    let outer = NoCollectGuard::new();
+    {
+        let _inner = NoCollectGuard::new();
+        let _a = Cc::new(0);
+    }
+    let _b = Cc::new(1);
+    assert_eq!(executions, executions_count().unwrap());
+
+    drop(outer);
+    assert_eq!(executions + 1, executions_count().unwrap());
+}
+
+#[test]
+fn no_deferred_collection() {
+    let _guard = always_collect(DeferredCollection::RunImmediately);
+
+    // Disable auto-collect inside the scope, so that no collection becomes due
+    let executions = executions_count().unwrap();
+    no_collect(|| {
+        config(|config| config.set_auto_collect(false)).unwrap();
+        let _a = Cc::new(0);
+        config(|config| config.set_auto_collect(true)).unwrap();
+    });
+    assert_eq!(executions, executions_count().unwrap());
+}
+
+#[test]
+fn wait_for_next_trigger() {
+    let _guard = always_collect(DeferredCollection::WaitForNextTrigger);
+
+    let executions = executions_count().unwrap();
+    no_collect(|| {
+        let _a = Cc::new(0);
+    });
+    assert_eq!(executions, executions_count().unwrap());
+
+    let _b = Cc::new(1);
+    assert_eq!(executions + 1, executions_count().unwrap());
+}
+
+#[test]
+fn no_collection_is_forced_when_suppressed() {
+    let _guard = limit_heap(2, OutOfMemoryPolicy::Error);
+
+    // Fill the heap with garbage
+    let a = new_node();
+    let b = new_node();
+    *a.next.borrow_mut() = Some(b.clone());
+    *b.next.borrow_mut() = Some(a.clone());
+    drop(a);
+    drop(b);
+
+    let executions = executions_count().unwrap();
+    let result = no_collect(|| Cc::try_new(Node::default()).map(drop));
+    assert!(result.is_err());
+    assert_eq!(executions, executions_count().unwrap());
+
+    collect_cycles();
+}
@@ -0,0 +1,131 @@
+use std::cell::{Cell, RefCell};
+
+use rust_cc::{collect_cycles, request_collection};
+#[cfg(feature = "finalization")]
+use rust_cc::{Cc, Context, Finalize, Trace};
+use rust_cc::state::{CollectionReport, CollectionRequest, CollectionTrigger, executions_count, is_collection_requested, last_collections, on_collection_end};
+
+mod common;
+
+thread_local! {
+    static REQUESTS: RefCell<Vec<CollectionRequest>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record_request() {
+    let request = request_collection().unwrap();
+    REQUESTS.with(|requests| requests.borrow_mut().push(request));
+}
+
+fn take_requests() -> Vec<CollectionRequest> {
+    REQUESTS.with(|requests| requests.take())
+}
+
+fn last_trigger() -> CollectionTrigger {
+    last_collections().unwrap().last().unwrap().trigger()
+}
+
+/// Requests a collection when finalized.
+#[cfg(feature = "finalization")]
+struct Requester {
+    cyclic: RefCell<Option<Cc<Requester>>>,
+}
+
+#[cfg(feature = "finalization")]
+unsafe impl Trace for Requester {
+    fn trace(&self, ctx: &mut Context<'_>) {
+        self.cyclic.trace(ctx);
+    }
+}
+
+#[cfg(feature = "finalization")]
+impl Finalize for Requester {
+    fn finalize(&self) {
+        record_request();
+    }
+}
+
+#[cfg(feature = "finalization")]
+fn drop_requester() {
+    let cc = Cc::new(Requester {
+        cyclic: RefCell::new(None),
+    });
+    *cc.cyclic.borrow_mut() = Some(cc.clone());
+}
+
+#[test]
+fn executed_immediately() {
+    let executions = executions_count().unwrap();
+
+    let CollectionRequest::Executed(report) = request_collection().unwrap() else {
+        panic!("The collection wasn't executed");
+    };
+    assert_eq!(CollectionTrigger::Requested, report.trigger());
+    assert_eq!(executions + 1, executions_count().unwrap());
+    assert!(!is_collection_requested().unwrap());
+}
+
+#[cfg(feature = "finalization")]
+#[test]
+fn requested_from_finalizer() {
+    take_requests();
+    collect_cycles();
+    let executions = executions_count().unwrap();
+
+    drop_requester();
+    collect_cycles();
+
+    // The requested collection is executed right after the manual one
+    assert_eq!(vec![CollectionRequest::Pending], take_requests());
+    assert_eq!(executions + 2, executions_count().unwrap());
+    assert_eq!(CollectionTrigger::Requested, last_trigger());
+    assert!(!is_collection_requested().unwrap());
+}
+
+#[cfg(feature = "finalization")]
+#[test]
+fn requested_from_requested_collection() {
+    take_requests();
+    collect_cycles();
+
+    drop_requester();
+    assert!(matches!(request_collection().unwrap(), CollectionRequest::Executed(_)));
+
+    // The request made by the finalizer is left to the next safe point
+    assert_eq!(vec![CollectionRequest::Pending], take_requests());
+    assert!(is_collection_requested().unwrap());
+
+    let executions = executions_count().unwrap();
+
+    #[cfg(feature = "auto-collect")]
+    let _ = Cc::new(0); // A collection may be automatically started, so the request is honored
+
+    #[cfg(not(feature = "auto-collect"))]
+    collect_cycles();
+
+    assert_eq!(executions + 1, executions_count().unwrap());
+    assert!(!is_collection_requested().unwrap());
+}
+
+#[test]
+fn requested_from_hook() {
+    thread_local! {
+        static REQUESTED: Cell<bool> = const { Cell::new(false) };
+    }
+
+    fn hook(trigger: CollectionTrigger, _: &CollectionReport) {
+        if trigger == CollectionTrigger::Manual && !REQUESTED.with(|requested| requested.replace(true)) {
+            record_request();
+        }
+    }
+
+    take_requests();
+    on_collection_end(Some(hook)).unwrap();
+    let executions = executions_count().unwrap();
+
+    collect_cycles();
+    on_collection_end(None).unwrap();
+
+    assert_eq!(vec![CollectionRequest::Pending], take_requests());
+    assert_eq!(executions + 2, executions_count().unwrap());
+    assert_eq!(CollectionTrigger::Requested, last_trigger());
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "auto-collect")]
+
+use std::num::NonZeroUsize;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use rust_cc::config::{config, Config, ConfigAccessError, GrowthStrategy, InvalidConfigError, replace, scoped};
+
+mod common;
+
+fn buffered_objects_threshold() -> Option<usize> {
+    config(|config| config.buffered_objects_threshold().map(NonZeroUsize::get)).unwrap()
+}
+
+fn with_buffered_objects_threshold(threshold: usize) -> Config {
+    Config::builder()
+        .buffered_objects_threshold(NonZeroUsize::new(threshold))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn builder() {
+    let config = Config::builder()
+        .auto_collect(false)
+        .adjustment_percent(0.5)
+        .growth_strategy(GrowthStrategy::Proportional { percent: 50 })
+        .min_bytes_threshold(10)
+        .max_bytes_threshold(1000)
+        .max_heap_bytes(Some(4096))
+        .build()
+        .unwrap();
+
+    assert!(!config.auto_collect());
+    assert_eq!(0.5, config.adjustment_percent());
+    assert_eq!(GrowthStrategy::Proportional { percent: 50 }, config.growth_strategy());
+    assert_eq!(10, config.min_bytes_threshold());
+    assert_eq!(1000, config.max_bytes_threshold());
+    assert_eq!(Some(4096), config.max_heap_bytes());
+}
+
+#[test]
+fn builder_validation() {
+    let error = Config::builder().adjustment_percent(-0.1).build().unwrap_err();
+    assert_eq!(InvalidConfigError::AdjustmentPercent(-0.1), error);
+
+    let error = Config::builder().min_bytes_threshold(0).build().unwrap_err();
+    assert_eq!(InvalidConfigError::ZeroMinBytesThreshold, error);
+
+    let error = Config::builder().min_bytes_threshold(200).max_bytes_threshold(100).build().unwrap_err();
+    assert_eq!(InvalidConfigError::BytesThresholdBounds { min: 200, max: 100 }, error);
+}
+
+#[test]
+fn replace_returns_previous_config() {
+    let old = replace(with_buffered_objects_threshold(7)).unwrap();
+    assert_eq!(Some(7), buffered_objects_threshold());
+
+    let temp = replace(old).unwrap();
+    assert_eq!(Some(7), temp.buffered_objects_threshold().map(NonZeroUsize::get));
+    assert_eq!(None, buffered_objects_threshold());
+}
+
+#[test]
+fn scoped_restores_config() {
+    let result = scoped(with_buffered_objects_threshold(3), || {
+        assert_eq!(Some(3), buffered_objects_threshold());
+
+        // Changes are discarded too
+        config(|config| config.set_auto_collect(false)).unwrap();
+        42
+    });
+
+    assert_eq!(42, result.unwrap());
+    assert_eq!(None, buffered_objects_threshold());
+    assert!(config(|config| config.auto_collect()).unwrap());
+}
+
+#[test]
+fn scoped_restores_config_on_panic() {
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let _ = scoped(with_buffered_objects_threshold(3), || {
+            panic!("panicking inside scoped");
+        });
+    }));
+
+    assert!(result.is_err());
+    assert_eq!(None, buffered_objects_threshold());
+}
+
+#[test]
+fn scoped_inside_config() {
+    let result = config(|_| scoped(Config::default(), || unreachable!())).unwrap();
+    assert!(matches!(result, Err(ConfigAccessError::ConcurrentAccessError)));
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "type-stats")]
+
+use std::any::type_name;
+use std::cell::RefCell;
+
+use rust_cc::{Cc, collect_cycles, Context, Finalize, Trace};
+use rust_cc::state::{type_stats, TypeStats};
+
+mod common;
+
+// Every test uses its own type, since statistics are shared between tests when critical-section is enabled
+macro_rules! node_type {
+    ($name:ident) => {
+        struct $name {
+            next: RefCell<Option<Cc<$name>>>,
+        }
+
+        unsafe impl Trace for $name {
+            fn trace(&self, ctx: &mut Context<'_>) {
+                self.next.trace(ctx);
+            }
+        }
+
+        impl Finalize for $name {}
+
+        impl $name {
+            fn new() -> Cc<$name> {
+                Cc::new($name {
+                    next: RefCell::new(None),
+                })
+            }
+        }
+    };
+}
+
+fn stats_of<T>() -> Option<TypeStats> {
+    type_stats().unwrap().into_iter().find(|stats| stats.type_name() == type_name::<T>())
+}
+
+node_type!(LiveNode);
+
+#[test]
+fn live_objects() {
+    assert!(stats_of::<LiveNode>().is_none());
+
+    let a = LiveNode::new();
+    let b = LiveNode::new();
+
+    let stats = stats_of::<LiveNode>().unwrap();
+    assert_eq!(2, stats.live_objects());
+    assert!(stats.live_bytes() >= 2 * size_of::<LiveNode>());
+    assert_eq!(0, stats.buffered());
+
+    drop(a);
+    let stats = stats_of::<LiveNode>().unwrap();
+    assert_eq!(1, stats.live_objects());
+    assert_eq!(1, stats.freed_by_refcount());
+    assert_eq!(0, stats.freed_by_collection());
+
+    drop(b);
+    let stats = stats_of::<LiveNode>().unwrap();
+    assert_eq!(0, stats.live_objects());
+    assert_eq!(0, stats.live_bytes());
+    assert_eq!(2, stats.freed_by_refcount());
+}
+
+node_type!(CycleNode);
+
+#[test]
+fn freed_by_collection() {
+    let a = CycleNode::new();
+    let b = CycleNode::new();
+    *a.next.borrow_mut() = Some(b.clone());
+    *b.next.borrow_mut() = Some(a.clone());
+    drop(a);
+    drop(b);
+
+    let stats = stats_of::<CycleNode>().unwrap();
+    assert_eq!(2, stats.live_objects());
+    assert_eq!(2, stats.buffered());
+
+    collect_cycles();
+
+    let stats = stats_of::<CycleNode>().unwrap();
+    assert_eq!(0, stats.live_objects());
+    assert_eq!(0, stats.live_bytes());
+    assert_eq!(2, stats.freed_by_collection());
+    assert_eq!(0, stats.freed_by_refcount());
+}
+
+node_type!(BufferedNode);
+
+#[test]
+fn buffered_count() {
+    let a = BufferedNode::new();
+    let b = a.clone();
+    let c = a.clone();
+
+    // Decrementing the counter of an already buffered object doesn't buffer it again
+    drop(b);
+    drop(c);
+    assert_eq!(1, stats_of::<BufferedNode>().unwrap().buffered());
+
+    // Cloning marks a as alive, removing it from the buffer
+    drop(a.clone());
+    assert_eq!(2, stats_of::<BufferedNode>().unwrap().buffered());
+
+    drop(a);
+    let stats = stats_of::<BufferedNode>().unwrap();
+    assert_eq!(0, stats.live_objects());
+    assert_eq!(1, stats.freed_by_refcount());
+}
+
+node_type!(LargeNode);
+node_type!(SmallNode);
+
+#[test]
+fn sorted_by_live_bytes() {
+    let large: Vec<_> = (0..10).map(|_| LargeNode::new()).collect();
+    let small = SmallNode::new();
+
+    let stats = type_stats().unwrap();
+    let large_index = stats.iter().position(|stats| stats.type_name() == type_name::<LargeNode>()).unwrap();
+    let small_index = stats.iter().position(|stats| stats.type_name() == type_name::<SmallNode>()).unwrap();
+    assert!(large_index < small_index);
+
+    drop(large);
+    drop(small);
+}
@@ -9,6 +9,8 @@
 
 use test_case::test_case;
 
+mod common;
+
 struct Ignored<T: Trace + 'static> {
     weak: Weak<T>,
     should_panic: bool,